@@ -0,0 +1,267 @@
+//! Java clients, using `long` for `u32` and `u64` and keeping the bits of a `u64` as they are.
+//!
+//! Tuples have no Java counterpart, so contracts using them cannot have a Java client.
+
+use std::fmt::Write;
+
+use crate::wire::{StructType, WireType};
+use crate::{identifier, ClientModel, GeneratedFile};
+
+const RUNTIME: [(&str, &str); 3] = [
+    ("Address.java", include_str!("runtime/Address.java")),
+    ("PbcReader.java", include_str!("runtime/PbcReader.java")),
+    ("PbcWriter.java", include_str!("runtime/PbcWriter.java")),
+];
+
+const RESERVED: &[&str] = &[
+    "abstract", "assert", "boolean", "break", "byte", "case", "catch", "char", "class", "const",
+    "continue", "default", "do", "double", "else", "enum", "extends", "final", "finally",
+    "float", "for", "goto", "if", "implements", "import", "instanceof", "int", "interface",
+    "long", "native", "new", "package", "private", "protected", "public", "return", "short",
+    "static", "strictfp", "super", "switch", "synchronized", "this", "throw", "throws",
+    "transient", "try", "void", "volatile", "while", "reader", "writer",
+];
+
+/// The runtime and the client of `model`, declared in `package` if given.
+pub fn generate(model: &ClientModel, package: Option<&str>) -> Result<Vec<GeneratedFile>, String> {
+    if model.structs.iter().any(|s| s.name == model.name) {
+        return Err(format!(
+            "The client class `{}` has the name of a struct of the contract",
+            model.name
+        ));
+    }
+    let header = match package {
+        Some(package) => format!("package {};\n\n", package),
+        None => String::new(),
+    };
+
+    let mut files: Vec<GeneratedFile> = RUNTIME
+        .iter()
+        .map(|(name, contents)| GeneratedFile {
+            name: name.to_string(),
+            contents: format!("{}{}", header, contents),
+        })
+        .collect();
+    files.push(GeneratedFile {
+        name: format!("{}.java", model.name),
+        contents: format!("{}{}", header, client(model)?),
+    });
+    Ok(files)
+}
+
+fn client(model: &ClientModel) -> Result<String, String> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Client of the contract {}, generated by pbc-client-generator from its ABI. Do not edit.",
+        model.name
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    for import in ["List", "Map", "Optional", "Set"] {
+        writeln!(out, "import java.util.{};", import).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "/** Encoding of the actions and decoding of the state of the contract. */"
+    )
+    .unwrap();
+    writeln!(out, "public final class {} {{", model.name).unwrap();
+    writeln!(out, "  private {}() {{}}", model.name).unwrap();
+
+    for struct_type in model.structs.iter() {
+        writeln!(out).unwrap();
+        write_struct(&mut out, struct_type)?;
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "  /** Reads the state of the contract. */").unwrap();
+    writeln!(
+        out,
+        "  public static {} decodeState(byte[] bytes) {{",
+        type_of(&model.state, false)?
+    )
+    .unwrap();
+    writeln!(out, "    PbcReader reader = new PbcReader(bytes);").unwrap();
+    writeln!(out, "    return {};", read(&model.state)?).unwrap();
+    writeln!(out, "  }}").unwrap();
+
+    for action in model.actions.iter() {
+        let mut parameters = Vec::new();
+        let mut writes = Vec::new();
+        for (name, ty) in action.arguments.iter() {
+            let name = identifier(name, RESERVED);
+            parameters.push(format!("{} {}", type_of(ty, false)?, name));
+            writes.push(write(ty, &name, 0)?);
+        }
+
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "  /** The RPC invoking the action {{@code {}}}. */",
+            action.name
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  public static byte[] {}({}) {{",
+            identifier(&action.name, RESERVED),
+            parameters.join(", ")
+        )
+        .unwrap();
+        writeln!(out, "    PbcWriter writer = new PbcWriter();").unwrap();
+        writeln!(out, "    writer.writeU32({:#010x}L);", action.shortname).unwrap();
+        for write in writes {
+            writeln!(out, "    {};", write).unwrap();
+        }
+        writeln!(out, "    return writer.toBytes();").unwrap();
+        writeln!(out, "  }}").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    Ok(out)
+}
+
+fn write_struct(out: &mut String, struct_type: &StructType) -> Result<(), String> {
+    let mut fields = Vec::new();
+    for (name, ty) in struct_type.fields.iter() {
+        fields.push((identifier(name, RESERVED), type_of(ty, false)?, ty));
+    }
+    let name = &struct_type.name;
+
+    writeln!(out, "  public static final class {} {{", name).unwrap();
+    for (field, java_type, _) in fields.iter() {
+        writeln!(out, "    public final {} {};", java_type, field).unwrap();
+    }
+    if !fields.is_empty() {
+        writeln!(out).unwrap();
+    }
+    let parameters: Vec<String> = fields
+        .iter()
+        .map(|(field, java_type, _)| format!("{} {}", java_type, field))
+        .collect();
+    writeln!(out, "    public {}({}) {{", name, parameters.join(", ")).unwrap();
+    for (field, _, _) in fields.iter() {
+        writeln!(out, "      this.{} = {};", field, field).unwrap();
+    }
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    public static {} read(PbcReader reader) {{", name).unwrap();
+    for (field, java_type, ty) in fields.iter() {
+        writeln!(out, "      {} {} = {};", java_type, field, read(ty)?).unwrap();
+    }
+    let names: Vec<&str> = fields.iter().map(|(field, _, _)| field.as_str()).collect();
+    writeln!(out, "      return new {}({});", name, names.join(", ")).unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    public void write(PbcWriter writer) {{").unwrap();
+    for (field, _, ty) in fields.iter() {
+        writeln!(out, "      {};", write(ty, &format!("this.{}", field), 0)?).unwrap();
+    }
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "  }}").unwrap();
+    Ok(())
+}
+
+fn unsupported_tuple() -> String {
+    String::from("Tuples are not supported by Java clients")
+}
+
+/// The Java type of `ty`, boxed if it is a type argument.
+fn type_of(ty: &WireType, boxed: bool) -> Result<String, String> {
+    Ok(match ty {
+        WireType::U32 | WireType::U64 | WireType::I64 if boxed => String::from("Long"),
+        WireType::U32 | WireType::U64 | WireType::I64 => String::from("long"),
+        WireType::I32 if boxed => String::from("Integer"),
+        WireType::I32 => String::from("int"),
+        WireType::String => String::from("String"),
+        WireType::Bytes | WireType::ByteArray(_) => String::from("byte[]"),
+        WireType::Array(element, _) | WireType::Vec(element) => {
+            format!("List<{}>", type_of(element, true)?)
+        }
+        WireType::Set(element) => format!("Set<{}>", type_of(element, true)?),
+        WireType::Option(value) => format!("Optional<{}>", type_of(value, true)?),
+        WireType::Map(key, value) => {
+            format!("Map<{}, {}>", type_of(key, true)?, type_of(value, true)?)
+        }
+        WireType::Tuple(_) => return Err(unsupported_tuple()),
+        WireType::Address => String::from("Address"),
+        WireType::Struct(name) => name.clone(),
+    })
+}
+
+/// Expression reading a value of type `ty` from `reader`.
+fn read(ty: &WireType) -> Result<String, String> {
+    Ok(match ty {
+        WireType::U32 => String::from("reader.readU32()"),
+        WireType::I32 => String::from("reader.readI32()"),
+        WireType::U64 => String::from("reader.readU64()"),
+        WireType::I64 => String::from("reader.readI64()"),
+        WireType::String => String::from("reader.readString()"),
+        WireType::Bytes => String::from("reader.readBytes()"),
+        WireType::ByteArray(len) => format!("reader.readByteArray({})", len),
+        WireType::Array(element, len) => {
+            format!("reader.readArray({}, () -> {})", len, read(element)?)
+        }
+        WireType::Vec(element) => format!("reader.readVec(() -> {})", read(element)?),
+        WireType::Option(value) => format!("reader.readOption(() -> {})", read(value)?),
+        WireType::Map(key, value) => format!(
+            "reader.readMap(() -> {}, () -> {})",
+            read(key)?,
+            read(value)?
+        ),
+        WireType::Set(element) => format!("reader.readSet(() -> {})", read(element)?),
+        WireType::Tuple(_) => return Err(unsupported_tuple()),
+        WireType::Address => String::from("reader.readAddress()"),
+        WireType::Struct(name) => format!("{}.read(reader)", name),
+    })
+}
+
+/// Statement writing `value` of type `ty` to `writer`, with lambdas nested `depth` deep.
+///
+/// Lambda parameters are numbered by depth, as Java forbids shadowing them.
+fn write(ty: &WireType, value: &str, depth: usize) -> Result<String, String> {
+    let element = format!("element{}", depth);
+    let nested = |inner: &WireType| write(inner, &element, depth + 1);
+    Ok(match ty {
+        WireType::U32 => format!("writer.writeU32({})", value),
+        WireType::I32 => format!("writer.writeI32({})", value),
+        WireType::U64 => format!("writer.writeU64({})", value),
+        WireType::I64 => format!("writer.writeI64({})", value),
+        WireType::String => format!("writer.writeString({})", value),
+        WireType::Bytes => format!("writer.writeBytes({})", value),
+        WireType::ByteArray(_) => format!("writer.writeByteArray({})", value),
+        WireType::Array(inner, _) => format!(
+            "writer.writeArray({}, {} -> {})",
+            value,
+            element,
+            nested(inner)?
+        ),
+        WireType::Vec(inner) => {
+            format!("writer.writeVec({}, {} -> {})", value, element, nested(inner)?)
+        }
+        WireType::Option(inner) => format!(
+            "writer.writeOption({}, {} -> {})",
+            value,
+            element,
+            nested(inner)?
+        ),
+        WireType::Map(key, inner) => {
+            let key_name = format!("key{}", depth);
+            format!(
+                "writer.writeMap({}, {} -> {}, {} -> {})",
+                value,
+                key_name,
+                write(key, &key_name, depth + 1)?,
+                element,
+                nested(inner)?
+            )
+        }
+        WireType::Set(inner) => {
+            format!("writer.writeSet({}, {} -> {})", value, element, nested(inner)?)
+        }
+        WireType::Tuple(_) => return Err(unsupported_tuple()),
+        WireType::Address => format!("writer.writeAddress({})", value),
+        WireType::Struct(_) => format!("{}.write(writer)", value),
+    })
+}
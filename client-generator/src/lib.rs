@@ -0,0 +1,185 @@
+//! Generation of TypeScript and Java clients from the ABI of a contract.
+//!
+//! A client consists of a runtime reading and writing the wire format of
+//! `pbc_contract_common::serialization`, and a file per contract with a class for every struct
+//! of the state, a function per action building its RPC with the shortname prefix, and a
+//! decoder of the state.
+
+use pbc_contract_common::abi::{ContractAbi, FunctionKind, SchemaAbi};
+
+use crate::wire::{StructType, WireType, WireTypes};
+
+pub mod java;
+pub mod typescript;
+pub mod wire;
+
+/// A source file of a generated client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub name: String,
+    pub contents: String,
+}
+
+/// An action of the contract, with the wire types of its arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActionModel {
+    pub name: String,
+    pub shortname: u32,
+    pub arguments: Vec<(String, WireType)>,
+}
+
+/// What a client is generated from: the contract reduced to the wire types of its state and of
+/// the arguments of its actions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientModel {
+    pub name: String,
+    pub state: WireType,
+    pub structs: Vec<StructType>,
+    pub actions: Vec<ActionModel>,
+}
+
+impl ClientModel {
+    /// The model of the client named `name` of the contract with ABI `abi`.
+    ///
+    /// Fails if the state or an argument has a type that clients cannot encode, or an action has
+    /// no shortname.
+    pub fn from_abi(abi: &ContractAbi, name: &str) -> Result<Self, String> {
+        let mut types = WireTypes::default();
+        let state = types
+            .resolve(&abi.state)
+            .map_err(|error| format!("State: {}", error))?;
+
+        let mut actions = Vec::new();
+        for function in abi.functions.iter() {
+            if function.kind != FunctionKind::Action {
+                continue;
+            }
+            let mut arguments = Vec::new();
+            for argument in function.arguments.iter() {
+                let ty = types.resolve(&argument.schema).map_err(|error| {
+                    format!(
+                        "Argument `{}` of `{}`: {}",
+                        argument.name, function.name, error
+                    )
+                })?;
+                arguments.push((argument.name.clone(), ty));
+            }
+            actions.push(ActionModel {
+                name: function.name.clone(),
+                shortname: function
+                    .shortname
+                    .ok_or_else(|| format!("Action `{}` has no shortname", function.name))?,
+                arguments,
+            });
+        }
+
+        Ok(ClientModel {
+            name: name.to_string(),
+            state,
+            structs: types.structs,
+            actions,
+        })
+    }
+}
+
+/// Parse an ABI exported either as json or in the binary format.
+pub fn read_abi(bytes: &[u8]) -> Result<ContractAbi, String> {
+    let json_start = bytes.iter().find(|byte| !byte.is_ascii_whitespace());
+    if json_start == Some(&b'{') {
+        let json = std::str::from_utf8(bytes).map_err(|error| error.to_string())?;
+        ContractAbi::from_json(json).map_err(|error| error.to_string())
+    } else {
+        ContractAbi::try_from_binary(bytes)
+    }
+}
+
+/// The name of the client of a contract, which is its state type without a `State` suffix.
+pub fn default_name(abi: &ContractAbi) -> String {
+    let type_name = match &abi.state {
+        SchemaAbi::Field {
+            type_name: Some(type_name),
+            ..
+        } => type_name.as_str(),
+        _ => "Contract",
+    };
+    match type_name.strip_suffix("State") {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => type_name.to_string(),
+    }
+}
+
+/// `snake_case` as `camelCase`, with tuple fields such as `0` named `field0`.
+fn camel_case(name: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("field{}", name);
+    }
+    let mut result = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = !result.is_empty();
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// `name` as an identifier that is not one of `reserved`.
+fn identifier(name: &str, reserved: &[&str]) -> String {
+    let name = camel_case(name);
+    if reserved.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pbc_contract_common::abi::{FunctionAbi, ABI_VERSION};
+
+    use super::*;
+
+    #[test]
+    fn names_are_camel_cased() {
+        assert_eq!(camel_case("total_supply"), "totalSupply");
+        assert_eq!(camel_case("_private"), "private");
+        assert_eq!(camel_case("0"), "field0");
+        assert_eq!(identifier("default", &["default"]), "default_");
+    }
+
+    #[test]
+    fn malformed_binary_abis_are_errors() {
+        assert_eq!(
+            read_abi(&[0, 0, 0, 2, 0, 0, 0, 1]),
+            Err(String::from("The ABI ends unexpectedly"))
+        );
+    }
+
+    #[test]
+    fn actions_without_a_shortname_are_errors() {
+        let abi = ContractAbi {
+            version: ABI_VERSION,
+            state: SchemaAbi::of::<u64>(),
+            functions: vec![FunctionAbi {
+                kind: FunctionKind::Action,
+                name: String::from("increment"),
+                shortname: None,
+                arguments: Vec::new(),
+                returns: None,
+                error: None,
+            }],
+            events: Vec::new(),
+            upgrade: None,
+        };
+
+        assert_eq!(
+            ClientModel::from_abi(&abi, "Counter"),
+            Err(String::from("Action `increment` has no shortname"))
+        );
+    }
+}
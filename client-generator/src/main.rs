@@ -0,0 +1,86 @@
+//! Generate a TypeScript or Java client from the ABI of a contract.
+//!
+//! ```text
+//! pbc-client-generator (typescript | java) <abi> <out-dir> [--name <name>] [--package <package>]
+//! ```
+//!
+//! The ABI is the json or binary export of the contract. The client is named after the state of
+//! the contract unless `--name` is given, and `--package` declares the package of Java clients.
+
+use std::path::PathBuf;
+use std::process::exit;
+
+use pbc_client_generator::{default_name, java, read_abi, typescript, ClientModel, GeneratedFile};
+
+const USAGE: &str =
+    "Usage: pbc-client-generator (typescript | java) <abi> <out-dir> [--name <name>] [--package <package>]";
+
+struct Arguments {
+    language: String,
+    abi: PathBuf,
+    out_dir: PathBuf,
+    name: Option<String>,
+    package: Option<String>,
+}
+
+fn parse_arguments(mut args: impl Iterator<Item = String>) -> Result<Arguments, String> {
+    let mut positional = Vec::new();
+    let mut name = None;
+    let mut package = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => name = Some(args.next().ok_or("Missing value of --name")?),
+            "--package" => package = Some(args.next().ok_or("Missing value of --package")?),
+            _ => positional.push(arg),
+        }
+    }
+    match <[String; 3]>::try_from(positional) {
+        Ok([language, abi, out_dir]) => Ok(Arguments {
+            language,
+            abi: PathBuf::from(abi),
+            out_dir: PathBuf::from(out_dir),
+            name,
+            package,
+        }),
+        Err(_) => Err(String::from(USAGE)),
+    }
+}
+
+fn run(arguments: Arguments) -> Result<Vec<PathBuf>, String> {
+    let bytes = std::fs::read(&arguments.abi)
+        .map_err(|error| format!("Cannot read {}: {}", arguments.abi.display(), error))?;
+    let abi = read_abi(&bytes)?;
+    let name = arguments.name.unwrap_or_else(|| default_name(&abi));
+    let model = ClientModel::from_abi(&abi, &name)?;
+
+    let files: Vec<GeneratedFile> = match arguments.language.as_str() {
+        "typescript" => typescript::generate(&model),
+        "java" => java::generate(&model, arguments.package.as_deref())?,
+        other => return Err(format!("Unknown language `{}`\n{}", other, USAGE)),
+    };
+
+    std::fs::create_dir_all(&arguments.out_dir).map_err(|error| error.to_string())?;
+    let mut written = Vec::new();
+    for file in files {
+        let path = arguments.out_dir.join(&file.name);
+        std::fs::write(&path, file.contents)
+            .map_err(|error| format!("Cannot write {}: {}", path.display(), error))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn main() {
+    let result = parse_arguments(std::env::args().skip(1)).and_then(run);
+    match result {
+        Ok(written) => {
+            for path in written {
+                println!("{}", path.display());
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1);
+        }
+    }
+}
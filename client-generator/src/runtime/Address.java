@@ -0,0 +1,40 @@
+// An address on the chain, as encoded by `pbc_contract_common::address::Address`.
+// Generated by pbc-client-generator. Do not edit.
+
+import java.util.Arrays;
+
+public final class Address {
+  public static final int ACCOUNT = 0;
+  public static final int SYSTEM_CONTRACT = 1;
+  public static final int PUBLIC_CONTRACT = 2;
+  public static final int ZK_CONTRACT = 3;
+
+  public final int type;
+  public final byte[] identifier;
+
+  public Address(int type, byte[] identifier) {
+    if (type < ACCOUNT || type > ZK_CONTRACT) {
+      throw new IllegalArgumentException("Unrecognized address type " + type);
+    }
+    if (identifier.length != 20) {
+      throw new IllegalArgumentException(
+          "Address identifiers are 20 bytes, got " + identifier.length);
+    }
+    this.type = type;
+    this.identifier = identifier.clone();
+  }
+
+  @Override
+  public boolean equals(Object other) {
+    if (!(other instanceof Address)) {
+      return false;
+    }
+    Address address = (Address) other;
+    return type == address.type && Arrays.equals(identifier, address.identifier);
+  }
+
+  @Override
+  public int hashCode() {
+    return 31 * type + Arrays.hashCode(identifier);
+  }
+}
@@ -0,0 +1,95 @@
+// Reading of the contract wire format, as implemented by `pbc_contract_common::serialization`.
+// All integers are big-endian, except the length of sets.
+// Generated by pbc-client-generator. Do not edit.
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.LinkedHashMap;
+import java.util.LinkedHashSet;
+import java.util.List;
+import java.util.Map;
+import java.util.Optional;
+import java.util.Set;
+import java.util.function.Supplier;
+
+public final class PbcReader {
+  private final ByteBuffer buffer;
+
+  public PbcReader(byte[] bytes) {
+    this.buffer = ByteBuffer.wrap(bytes).order(ByteOrder.BIG_ENDIAN);
+  }
+
+  public int readU8() {
+    return buffer.get() & 0xff;
+  }
+
+  public long readU32() {
+    return buffer.getInt() & 0xffffffffL;
+  }
+
+  public int readI32() {
+    return buffer.getInt();
+  }
+
+  /** Reads a `u64`, which is returned with the same bits as a `long`. */
+  public long readU64() {
+    return buffer.getLong();
+  }
+
+  public long readI64() {
+    return buffer.getLong();
+  }
+
+  public byte[] readByteArray(int length) {
+    byte[] result = new byte[length];
+    buffer.get(result);
+    return result;
+  }
+
+  public byte[] readBytes() {
+    return readByteArray(Math.toIntExact(readU32()));
+  }
+
+  public String readString() {
+    return new String(readBytes(), StandardCharsets.UTF_8);
+  }
+
+  public Address readAddress() {
+    int type = readU8();
+    return new Address(type, readByteArray(20));
+  }
+
+  public <T> List<T> readArray(int length, Supplier<T> readElement) {
+    List<T> result = new ArrayList<>(length);
+    for (int i = 0; i < length; i++) {
+      result.add(readElement.get());
+    }
+    return result;
+  }
+
+  public <T> List<T> readVec(Supplier<T> readElement) {
+    return readArray(readI32(), readElement);
+  }
+
+  public <T> Optional<T> readOption(Supplier<T> readValue) {
+    return readU8() == 0 ? Optional.empty() : Optional.of(readValue.get());
+  }
+
+  public <K, V> Map<K, V> readMap(Supplier<K> readKey, Supplier<V> readValue) {
+    long length = readU32();
+    Map<K, V> result = new LinkedHashMap<>();
+    for (long i = 0; i < length; i++) {
+      K key = readKey.get();
+      result.put(key, readValue.get());
+    }
+    return result;
+  }
+
+  public <T> Set<T> readSet(Supplier<T> readElement) {
+    int length = buffer.order(ByteOrder.LITTLE_ENDIAN).getInt();
+    buffer.order(ByteOrder.BIG_ENDIAN);
+    return new LinkedHashSet<>(readArray(length, readElement));
+  }
+}
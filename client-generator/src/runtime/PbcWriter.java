@@ -0,0 +1,94 @@
+// Writing of the contract wire format, as implemented by `pbc_contract_common::serialization`.
+// All integers are big-endian, except the length of sets.
+// Generated by pbc-client-generator. Do not edit.
+
+import java.io.ByteArrayOutputStream;
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.List;
+import java.util.Map;
+import java.util.Optional;
+import java.util.Set;
+import java.util.function.Consumer;
+
+public final class PbcWriter {
+  private final ByteArrayOutputStream output = new ByteArrayOutputStream();
+
+  public void writeU8(int value) {
+    output.write(value);
+  }
+
+  public void writeU32(long value) {
+    writeI32((int) value);
+  }
+
+  public void writeI32(int value) {
+    writeByteArray(ByteBuffer.allocate(4).order(ByteOrder.BIG_ENDIAN).putInt(value).array());
+  }
+
+  /** Writes a `u64` with the same bits as `value`. */
+  public void writeU64(long value) {
+    writeI64(value);
+  }
+
+  public void writeI64(long value) {
+    writeByteArray(ByteBuffer.allocate(8).order(ByteOrder.BIG_ENDIAN).putLong(value).array());
+  }
+
+  public void writeByteArray(byte[] value) {
+    output.write(value, 0, value.length);
+  }
+
+  public void writeBytes(byte[] value) {
+    writeU32(value.length);
+    writeByteArray(value);
+  }
+
+  public void writeString(String value) {
+    writeBytes(value.getBytes(StandardCharsets.UTF_8));
+  }
+
+  public void writeAddress(Address value) {
+    writeU8(value.type);
+    writeByteArray(value.identifier);
+  }
+
+  public <T> void writeArray(List<T> value, Consumer<T> writeElement) {
+    value.forEach(writeElement);
+  }
+
+  public <T> void writeVec(List<T> value, Consumer<T> writeElement) {
+    writeI32(value.size());
+    writeArray(value, writeElement);
+  }
+
+  public <T> void writeOption(Optional<T> value, Consumer<T> writeValue) {
+    if (value.isPresent()) {
+      writeU8(1);
+      writeValue.accept(value.get());
+    } else {
+      writeU8(0);
+    }
+  }
+
+  public <K, V> void writeMap(Map<K, V> value, Consumer<K> writeKey, Consumer<V> writeValue) {
+    writeU32(value.size());
+    value.forEach(
+        (key, entry) -> {
+          writeKey.accept(key);
+          writeValue.accept(entry);
+        });
+  }
+
+  /** Writes a set, whose elements must iterate in ascending order as contracts reject others. */
+  public <T> void writeSet(Set<T> value, Consumer<T> writeElement) {
+    writeByteArray(
+        ByteBuffer.allocate(4).order(ByteOrder.LITTLE_ENDIAN).putInt(value.size()).array());
+    value.forEach(writeElement);
+  }
+
+  public byte[] toBytes() {
+    return output.toByteArray();
+  }
+}
@@ -0,0 +1,210 @@
+// Reading and writing of the contract wire format, as implemented by
+// `pbc_contract_common::serialization`. All integers are big-endian, except the length of sets.
+// Generated by pbc-client-generator. Do not edit.
+
+export enum AddressType {
+  Account = 0,
+  SystemContract = 1,
+  PublicContract = 2,
+  ZkContract = 3,
+}
+
+export class Address {
+  constructor(
+    public readonly type: AddressType,
+    public readonly identifier: Uint8Array,
+  ) {
+    if (identifier.length !== 20) {
+      throw new Error(`Address identifiers are 20 bytes, got ${identifier.length}`);
+    }
+  }
+}
+
+export class AbiReader {
+  private offset = 0;
+  private readonly view: DataView;
+
+  constructor(private readonly bytes: Uint8Array) {
+    this.view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  }
+
+  readU8(): number {
+    const value = this.view.getUint8(this.offset);
+    this.offset += 1;
+    return value;
+  }
+
+  readU32(): number {
+    const value = this.view.getUint32(this.offset, false);
+    this.offset += 4;
+    return value;
+  }
+
+  readI32(): number {
+    const value = this.view.getInt32(this.offset, false);
+    this.offset += 4;
+    return value;
+  }
+
+  readU64(): bigint {
+    const value = this.view.getBigUint64(this.offset, false);
+    this.offset += 8;
+    return value;
+  }
+
+  readI64(): bigint {
+    const value = this.view.getBigInt64(this.offset, false);
+    this.offset += 8;
+    return value;
+  }
+
+  readByteArray(length: number): Uint8Array {
+    if (this.offset + length > this.bytes.length) {
+      throw new RangeError("Read past the end of the buffer");
+    }
+    const value = this.bytes.slice(this.offset, this.offset + length);
+    this.offset += length;
+    return value;
+  }
+
+  readBytes(): Uint8Array {
+    return this.readByteArray(this.readU32());
+  }
+
+  readString(): string {
+    return new TextDecoder().decode(this.readBytes());
+  }
+
+  readAddress(): Address {
+    const type = this.readU8();
+    if (type > AddressType.ZkContract) {
+      throw new Error(`Unrecognized address type ${type}`);
+    }
+    return new Address(type, this.readByteArray(20));
+  }
+
+  readArray<T>(length: number, readElement: () => T): T[] {
+    const result: T[] = [];
+    for (let i = 0; i < length; i++) {
+      result.push(readElement());
+    }
+    return result;
+  }
+
+  readVec<T>(readElement: () => T): T[] {
+    return this.readArray(this.readI32(), readElement);
+  }
+
+  readOption<T>(readValue: () => T): T | undefined {
+    return this.readU8() === 0 ? undefined : readValue();
+  }
+
+  readMap<K, V>(readKey: () => K, readValue: () => V): Array<[K, V]> {
+    const length = this.readU32();
+    const result: Array<[K, V]> = [];
+    for (let i = 0; i < length; i++) {
+      const key = readKey();
+      const value = readValue();
+      result.push([key, value]);
+    }
+    return result;
+  }
+
+  readSet<T>(readElement: () => T): T[] {
+    const length = this.view.getUint32(this.offset, true);
+    this.offset += 4;
+    return this.readArray(length, readElement);
+  }
+}
+
+export class AbiWriter {
+  private readonly chunks: number[] = [];
+
+  writeU8(value: number): void {
+    this.chunks.push(value & 0xff);
+  }
+
+  writeU32(value: number): void {
+    const bytes = new Uint8Array(4);
+    new DataView(bytes.buffer).setUint32(0, value, false);
+    this.writeByteArray(bytes);
+  }
+
+  writeI32(value: number): void {
+    const bytes = new Uint8Array(4);
+    new DataView(bytes.buffer).setInt32(0, value, false);
+    this.writeByteArray(bytes);
+  }
+
+  writeU64(value: bigint): void {
+    const bytes = new Uint8Array(8);
+    new DataView(bytes.buffer).setBigUint64(0, value, false);
+    this.writeByteArray(bytes);
+  }
+
+  writeI64(value: bigint): void {
+    const bytes = new Uint8Array(8);
+    new DataView(bytes.buffer).setBigInt64(0, value, false);
+    this.writeByteArray(bytes);
+  }
+
+  writeByteArray(value: Uint8Array): void {
+    value.forEach((byte) => this.chunks.push(byte));
+  }
+
+  writeBytes(value: Uint8Array): void {
+    this.writeU32(value.length);
+    this.writeByteArray(value);
+  }
+
+  writeString(value: string): void {
+    this.writeBytes(new TextEncoder().encode(value));
+  }
+
+  writeAddress(value: Address): void {
+    this.writeU8(value.type);
+    this.writeByteArray(value.identifier);
+  }
+
+  writeArray<T>(value: T[], writeElement: (element: T) => void): void {
+    value.forEach(writeElement);
+  }
+
+  writeVec<T>(value: T[], writeElement: (element: T) => void): void {
+    this.writeI32(value.length);
+    this.writeArray(value, writeElement);
+  }
+
+  writeOption<T>(value: T | undefined, writeValue: (value: T) => void): void {
+    if (value === undefined) {
+      this.writeU8(0);
+    } else {
+      this.writeU8(1);
+      writeValue(value);
+    }
+  }
+
+  writeMap<K, V>(
+    value: Array<[K, V]>,
+    writeKey: (key: K) => void,
+    writeValue: (value: V) => void,
+  ): void {
+    this.writeU32(value.length);
+    value.forEach(([key, entry]) => {
+      writeKey(key);
+      writeValue(entry);
+    });
+  }
+
+  /** Writes a set, whose elements must be in ascending order as contracts reject others. */
+  writeSet<T>(value: T[], writeElement: (element: T) => void): void {
+    const length = new Uint8Array(4);
+    new DataView(length.buffer).setUint32(0, value.length, true);
+    this.writeByteArray(length);
+    this.writeArray(value, writeElement);
+  }
+
+  toBytes(): Uint8Array {
+    return Uint8Array.from(this.chunks);
+  }
+}
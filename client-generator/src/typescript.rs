@@ -0,0 +1,237 @@
+//! TypeScript clients, using `bigint` for 64-bit integers.
+
+use std::fmt::Write;
+
+use crate::wire::{StructType, WireType};
+use crate::{identifier, ClientModel, GeneratedFile};
+
+/// The file name of the runtime, imported by every client.
+pub const RUNTIME_FILE: &str = "pbc-serialization.ts";
+
+const RUNTIME: &str = include_str!("runtime/pbc-serialization.ts");
+
+const RESERVED: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "enum", "export", "extends", "false", "finally", "for", "function", "if", "import",
+    "in", "instanceof", "new", "null", "return", "super", "switch", "this", "throw", "true",
+    "try", "typeof", "var", "void", "while", "with", "reader", "writer",
+];
+
+/// The runtime and the client of `model`.
+pub fn generate(model: &ClientModel) -> Vec<GeneratedFile> {
+    vec![
+        GeneratedFile {
+            name: RUNTIME_FILE.to_string(),
+            contents: RUNTIME.to_string(),
+        },
+        GeneratedFile {
+            name: format!("{}.ts", model.name),
+            contents: client(model),
+        },
+    ]
+}
+
+fn client(model: &ClientModel) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Client of the contract {}, generated by pbc-client-generator from its ABI. Do not edit.",
+        model.name
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "import {{ AbiReader, AbiWriter, Address }} from \"./{}\";",
+        RUNTIME_FILE.trim_end_matches(".ts")
+    )
+    .unwrap();
+
+    for struct_type in model.structs.iter() {
+        writeln!(out).unwrap();
+        write_struct(&mut out, struct_type);
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "/** Reads the state of the contract. */").unwrap();
+    writeln!(
+        out,
+        "export function decodeState(bytes: Uint8Array): {} {{",
+        type_of(&model.state)
+    )
+    .unwrap();
+    writeln!(out, "  const reader = new AbiReader(bytes);").unwrap();
+    writeln!(out, "  return {};", read(&model.state)).unwrap();
+    writeln!(out, "}}").unwrap();
+
+    for action in model.actions.iter() {
+        let arguments: Vec<(String, &WireType)> = action
+            .arguments
+            .iter()
+            .map(|(name, ty)| (identifier(name, RESERVED), ty))
+            .collect();
+        let parameters: Vec<String> = arguments
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, type_of(ty)))
+            .collect();
+
+        writeln!(out).unwrap();
+        writeln!(out, "/** The RPC invoking the action `{}`. */", action.name).unwrap();
+        writeln!(
+            out,
+            "export function {}({}): Uint8Array {{",
+            identifier(&action.name, RESERVED),
+            parameters.join(", ")
+        )
+        .unwrap();
+        writeln!(out, "  const writer = new AbiWriter();").unwrap();
+        writeln!(out, "  writer.writeU32({:#010x});", action.shortname).unwrap();
+        for (name, ty) in arguments.iter() {
+            writeln!(out, "  {};", write(ty, name, 0)).unwrap();
+        }
+        writeln!(out, "  return writer.toBytes();").unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+    out
+}
+
+fn write_struct(out: &mut String, struct_type: &StructType) {
+    let fields: Vec<(String, &WireType)> = struct_type
+        .fields
+        .iter()
+        .map(|(name, ty)| (identifier(name, RESERVED), ty))
+        .collect();
+    let name = &struct_type.name;
+
+    writeln!(out, "export class {} {{", name).unwrap();
+    writeln!(out, "  constructor(").unwrap();
+    for (field, ty) in fields.iter() {
+        writeln!(out, "    public readonly {}: {},", field, type_of(ty)).unwrap();
+    }
+    writeln!(out, "  ) {{}}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "  static read(reader: AbiReader): {} {{", name).unwrap();
+    for (field, ty) in fields.iter() {
+        writeln!(out, "    const {} = {};", field, read(ty)).unwrap();
+    }
+    let names: Vec<&str> = fields.iter().map(|(field, _)| field.as_str()).collect();
+    writeln!(out, "    return new {}({});", name, names.join(", ")).unwrap();
+    writeln!(out, "  }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "  write(writer: AbiWriter): void {{").unwrap();
+    for (field, ty) in fields.iter() {
+        writeln!(out, "    {};", write(ty, &format!("this.{}", field), 0)).unwrap();
+    }
+    writeln!(out, "  }}").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+fn type_of(ty: &WireType) -> String {
+    match ty {
+        WireType::U32 | WireType::I32 => String::from("number"),
+        WireType::U64 | WireType::I64 => String::from("bigint"),
+        WireType::String => String::from("string"),
+        WireType::Bytes | WireType::ByteArray(_) => String::from("Uint8Array"),
+        WireType::Array(element, _) | WireType::Vec(element) | WireType::Set(element) => {
+            format!("Array<{}>", type_of(element))
+        }
+        WireType::Option(value) => format!("{} | undefined", type_of(value)),
+        WireType::Map(key, value) => format!("Array<[{}, {}]>", type_of(key), type_of(value)),
+        WireType::Tuple(elements) => {
+            let elements: Vec<String> = elements.iter().map(type_of).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        WireType::Address => String::from("Address"),
+        WireType::Struct(name) => name.clone(),
+    }
+}
+
+/// Expression reading a value of type `ty` from `reader`.
+fn read(ty: &WireType) -> String {
+    match ty {
+        WireType::U32 => String::from("reader.readU32()"),
+        WireType::I32 => String::from("reader.readI32()"),
+        WireType::U64 => String::from("reader.readU64()"),
+        WireType::I64 => String::from("reader.readI64()"),
+        WireType::String => String::from("reader.readString()"),
+        WireType::Bytes => String::from("reader.readBytes()"),
+        WireType::ByteArray(len) => format!("reader.readByteArray({})", len),
+        WireType::Array(element, len) => {
+            format!("reader.readArray({}, () => {})", len, read(element))
+        }
+        WireType::Vec(element) => format!("reader.readVec(() => {})", read(element)),
+        WireType::Option(value) => format!("reader.readOption(() => {})", read(value)),
+        WireType::Map(key, value) => format!(
+            "reader.readMap(() => {}, () => {})",
+            read(key),
+            read(value)
+        ),
+        WireType::Set(element) => format!("reader.readSet(() => {})", read(element)),
+        WireType::Tuple(elements) => {
+            // Array literals are evaluated left to right, matching the order on the wire.
+            let elements: Vec<String> = elements.iter().map(read).collect();
+            format!("[{}] as {}", elements.join(", "), type_of(ty))
+        }
+        WireType::Address => String::from("reader.readAddress()"),
+        WireType::Struct(name) => format!("{}.read(reader)", name),
+    }
+}
+
+/// Expression writing `value` of type `ty` to `writer`, with lambdas nested `depth` deep.
+fn write(ty: &WireType, value: &str, depth: usize) -> String {
+    let element = format!("element{}", depth);
+    match ty {
+        WireType::U32 => format!("writer.writeU32({})", value),
+        WireType::I32 => format!("writer.writeI32({})", value),
+        WireType::U64 => format!("writer.writeU64({})", value),
+        WireType::I64 => format!("writer.writeI64({})", value),
+        WireType::String => format!("writer.writeString({})", value),
+        WireType::Bytes => format!("writer.writeBytes({})", value),
+        WireType::ByteArray(_) => format!("writer.writeByteArray({})", value),
+        WireType::Array(inner, _) => format!(
+            "writer.writeArray({}, ({}) => {})",
+            value,
+            element,
+            write(inner, &element, depth + 1)
+        ),
+        WireType::Vec(inner) => format!(
+            "writer.writeVec({}, ({}) => {})",
+            value,
+            element,
+            write(inner, &element, depth + 1)
+        ),
+        WireType::Option(inner) => format!(
+            "writer.writeOption({}, ({}) => {})",
+            value,
+            element,
+            write(inner, &element, depth + 1)
+        ),
+        WireType::Map(key, inner) => {
+            let key_name = format!("key{}", depth);
+            format!(
+                "writer.writeMap({}, ({}) => {}, ({}) => {})",
+                value,
+                key_name,
+                write(key, &key_name, depth + 1),
+                element,
+                write(inner, &element, depth + 1)
+            )
+        }
+        WireType::Set(inner) => format!(
+            "writer.writeSet({}, ({}) => {})",
+            value,
+            element,
+            write(inner, &element, depth + 1)
+        ),
+        WireType::Tuple(elements) => {
+            let writes: Vec<String> = elements
+                .iter()
+                .enumerate()
+                .map(|(index, inner)| write(inner, &format!("{}[{}]", value, index), depth))
+                .collect();
+            format!("({})", writes.join(", "))
+        }
+        WireType::Address => format!("writer.writeAddress({})", value),
+        WireType::Struct(_) => format!("{}.write(writer)", value),
+    }
+}
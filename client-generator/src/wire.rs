@@ -0,0 +1,257 @@
+//! The wire format of the types appearing in a contract ABI.
+//!
+//! Only types with a `ReadWrite` implementation in `pbc_contract_common::serialization` can be
+//! encoded by clients. Every other type is reported as an error instead of generating a client
+//! that disagrees with the contract.
+
+use pbc_contract_common::abi::SchemaAbi;
+use reflection::Type;
+
+/// How a value is laid out on the wire, mirroring the `ReadWrite` implementations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WireType {
+    /// Big-endian `u32`.
+    U32,
+    /// Big-endian `i32`.
+    I32,
+    /// Big-endian `u64`.
+    U64,
+    /// Big-endian `i64`.
+    I64,
+    /// UTF-8 bytes prefixed by their `u32` length.
+    String,
+    /// `Vec<u8>`, prefixed by its `u32` length.
+    Bytes,
+    /// `[u8; N]`, without a length prefix.
+    ByteArray(usize),
+    /// `[T; N]`, element by element without a length prefix.
+    Array(Box<WireType>, usize),
+    /// `Vec<T>`, prefixed by its `i32` length.
+    Vec(Box<WireType>),
+    /// A `0` byte for `None`, or a `1` byte followed by the value.
+    Option(Box<WireType>),
+    /// `BTreeMap<K, V>`, prefixed by its `u32` length and ordered by key.
+    Map(Box<WireType>, Box<WireType>),
+    /// `BTreeSet<T>`, prefixed by its little-endian `u32` length and ordered.
+    Set(Box<WireType>),
+    /// Tuples, element by element.
+    Tuple(Vec<WireType>),
+    /// The address kind as a byte, followed by the 20 bytes of the identifier.
+    Address,
+    /// A struct declared in [`WireTypes::structs`], field by field.
+    Struct(String),
+}
+
+/// A struct of the ABI with the wire types of its fields, in declaration order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<(String, WireType)>,
+}
+
+/// The structs encountered while resolving wire types, with every struct listed after the
+/// structs of its fields.
+#[derive(Default)]
+pub struct WireTypes {
+    pub structs: Vec<StructType>,
+}
+
+impl WireTypes {
+    /// The wire type of `schema`, declaring the structs it contains.
+    pub fn resolve(&mut self, schema: &SchemaAbi) -> Result<WireType, String> {
+        let (ty, type_name, members) = match schema {
+            SchemaAbi::Field {
+                ty,
+                type_name,
+                members,
+                ..
+            } => (*ty, type_name.as_deref().unwrap_or("_"), members),
+            SchemaAbi::Variant { name, .. } => {
+                return Err(format!("Expected a type, found the variant `{}`", name))
+            }
+        };
+
+        Ok(match ty {
+            Type::U32 => WireType::U32,
+            Type::I32 => WireType::I32,
+            Type::U64 => WireType::U64,
+            Type::I64 => WireType::I64,
+            Type::String => WireType::String,
+            Type::Vec => {
+                let element = &parameters(type_name, members, 1)?[0];
+                if is_u8(element) {
+                    WireType::Bytes
+                } else {
+                    WireType::Vec(Box::new(self.resolve(element)?))
+                }
+            }
+            Type::Array => {
+                let len = array_length(type_name)?;
+                let element = &parameters(type_name, members, 1)?[0];
+                if is_u8(element) {
+                    WireType::ByteArray(len)
+                } else {
+                    WireType::Array(Box::new(self.resolve(element)?), len)
+                }
+            }
+            Type::Option => WireType::Option(Box::new(self.resolve(some_value(members)?)?)),
+            Type::BTreeMap => {
+                let entry = parameters(type_name, members, 2)?;
+                WireType::Map(
+                    Box::new(self.resolve(&entry[0])?),
+                    Box::new(self.resolve(&entry[1])?),
+                )
+            }
+            Type::BTreeSet => {
+                let element = &parameters(type_name, members, 1)?[0];
+                WireType::Set(Box::new(self.resolve(element)?))
+            }
+            Type::Tuple => WireType::Tuple(
+                members
+                    .iter()
+                    .map(|member| self.resolve(member))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Type::Enum if type_name == "Address" => WireType::Address,
+            Type::Struct => {
+                if !self.structs.iter().any(|s| s.name == type_name) {
+                    let fields = members
+                        .iter()
+                        .map(|member| Ok((member_name(member), self.resolve(member)?)))
+                        .collect::<Result<_, String>>()?;
+                    self.structs.push(StructType {
+                        name: type_name.to_string(),
+                        fields,
+                    });
+                }
+                WireType::Struct(type_name.to_string())
+            }
+            _ => {
+                return Err(format!(
+                    "Type `{}` has no wire format that clients can encode",
+                    type_name
+                ))
+            }
+        })
+    }
+}
+
+fn is_u8(schema: &SchemaAbi) -> bool {
+    matches!(schema, SchemaAbi::Field { ty: Type::U8, .. })
+}
+
+fn member_name(schema: &SchemaAbi) -> String {
+    match schema {
+        SchemaAbi::Field { name, .. } | SchemaAbi::Variant { name, .. } => name.clone(),
+    }
+}
+
+/// The `count` type parameters of a container type, which an ABI may have left out.
+fn parameters<'a>(
+    type_name: &str,
+    members: &'a [SchemaAbi],
+    count: usize,
+) -> Result<&'a [SchemaAbi], String> {
+    if members.len() == count {
+        Ok(members)
+    } else {
+        Err(format!(
+            "Type `{}` has {} type parameters instead of {}",
+            type_name,
+            members.len(),
+            count
+        ))
+    }
+}
+
+/// The length `N` of an array type named `[T; N]`.
+fn array_length(type_name: &str) -> Result<usize, String> {
+    type_name
+        .trim_end_matches(']')
+        .rsplit(';')
+        .next()
+        .and_then(|len| len.trim().parse().ok())
+        .ok_or_else(|| format!("Cannot read the length of the array `{}`", type_name))
+}
+
+/// The value of the `Some` variant of an `Option` schema.
+fn some_value(members: &[SchemaAbi]) -> Result<&SchemaAbi, String> {
+    members
+        .iter()
+        .find_map(|member| match member {
+            SchemaAbi::Variant { name, members } if name == "Some" => members.first(),
+            _ => None,
+        })
+        .ok_or_else(|| String::from("Option without a `Some` variant"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pbc_contract_common::address::Address;
+
+    use super::*;
+
+    #[test]
+    fn resolves_containers_of_the_wire_format() {
+        let mut types = WireTypes::default();
+
+        assert_eq!(
+            types.resolve(&SchemaAbi::of::<BTreeMap<Address, Vec<u8>>>()),
+            Ok(WireType::Map(
+                Box::new(WireType::Address),
+                Box::new(WireType::Bytes)
+            ))
+        );
+        assert_eq!(
+            types.resolve(&SchemaAbi::of::<Option<[u64; 3]>>()),
+            Ok(WireType::Option(Box::new(WireType::Array(
+                Box::new(WireType::U64),
+                3
+            ))))
+        );
+        assert_eq!(
+            types.resolve(&SchemaAbi::of::<[u8; 16]>()),
+            Ok(WireType::ByteArray(16))
+        );
+    }
+
+    #[test]
+    fn rejects_types_without_read_write() {
+        let mut types = WireTypes::default();
+
+        assert_eq!(
+            types.resolve(&SchemaAbi::of::<bool>()),
+            Err(String::from(
+                "Type `bool` has no wire format that clients can encode"
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_containers_without_their_type_parameters() {
+        let mut types = WireTypes::default();
+        let mut map = SchemaAbi::of::<BTreeMap<u32, u64>>();
+        if let SchemaAbi::Field { members, .. } = &mut map {
+            members.pop();
+        }
+        let mut vec = SchemaAbi::of::<Vec<u8>>();
+        if let SchemaAbi::Field { members, .. } = &mut vec {
+            members.clear();
+        }
+
+        assert_eq!(
+            types.resolve(&map),
+            Err(String::from(
+                "Type `BTreeMap<u32,u64>` has 1 type parameters instead of 2"
+            ))
+        );
+        assert_eq!(
+            types.resolve(&vec),
+            Err(String::from(
+                "Type `Vec<u8>` has 0 type parameters instead of 1"
+            ))
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! The clients generated for the token contract match the checked-in golden files.
+//!
+//! Run with `GOLDEN=overwrite` to regenerate the golden files after an intended change.
+
+extern crate pbc_client_generator;
+
+use std::path::Path;
+
+use pbc_client_generator::{java, read_abi, typescript, ClientModel, GeneratedFile};
+
+fn token_contract_model() -> ClientModel {
+    let abi = read_abi(include_bytes!("golden/token_contract.abi.json")).unwrap();
+    ClientModel::from_abi(&abi, "TokenContract").unwrap()
+}
+
+fn check_golden(files: Vec<GeneratedFile>, name: &str) {
+    let file = files.into_iter().find(|file| file.name == name).unwrap();
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(&file.name);
+
+    if std::env::var("GOLDEN").as_deref() == Ok("overwrite") {
+        std::fs::write(&path, &file.contents).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        golden == file.contents,
+        "{} differs from the generated client; rerun with GOLDEN=overwrite if intended",
+        path.display()
+    );
+}
+
+#[test]
+fn typescript_client_matches_golden_file() {
+    check_golden(
+        typescript::generate(&token_contract_model()),
+        "TokenContract.ts",
+    );
+}
+
+#[test]
+fn java_client_matches_golden_file() {
+    check_golden(
+        java::generate(&token_contract_model(), None).unwrap(),
+        "TokenContract.java",
+    );
+}
+
+#[test]
+fn java_rejects_tuples() {
+    let mut model = token_contract_model();
+    model.actions[0].arguments[0].1 = pbc_client_generator::wire::WireType::Tuple(vec![]);
+
+    assert_eq!(
+        java::generate(&model, None),
+        Err(String::from("Tuples are not supported by Java clients"))
+    );
+}
@@ -0,0 +1,59 @@
+// Client of the contract TokenContract, generated by pbc-client-generator from its ABI. Do not edit.
+
+import java.util.List;
+import java.util.Map;
+import java.util.Optional;
+import java.util.Set;
+
+/** Encoding of the actions and decoding of the state of the contract. */
+public final class TokenContract {
+  private TokenContract() {}
+
+  public static final class TokenContractState {
+    public final byte[] symbol;
+    public final long totalSupply;
+    public final Map<Address, Long> balances;
+
+    public TokenContractState(byte[] symbol, long totalSupply, Map<Address, Long> balances) {
+      this.symbol = symbol;
+      this.totalSupply = totalSupply;
+      this.balances = balances;
+    }
+
+    public static TokenContractState read(PbcReader reader) {
+      byte[] symbol = reader.readByteArray(16);
+      long totalSupply = reader.readU64();
+      Map<Address, Long> balances = reader.readMap(() -> reader.readAddress(), () -> reader.readU64());
+      return new TokenContractState(symbol, totalSupply, balances);
+    }
+
+    public void write(PbcWriter writer) {
+      writer.writeByteArray(this.symbol);
+      writer.writeU64(this.totalSupply);
+      writer.writeMap(this.balances, key0 -> writer.writeAddress(key0), element0 -> writer.writeU64(element0));
+    }
+  }
+
+  /** Reads the state of the contract. */
+  public static TokenContractState decodeState(byte[] bytes) {
+    PbcReader reader = new PbcReader(bytes);
+    return TokenContractState.read(reader);
+  }
+
+  /** The RPC invoking the action {@code mint}. */
+  public static byte[] mint(long amount) {
+    PbcWriter writer = new PbcWriter();
+    writer.writeU32(0xbb176fdcL);
+    writer.writeU64(amount);
+    return writer.toBytes();
+  }
+
+  /** The RPC invoking the action {@code transfer}. */
+  public static byte[] transfer(Address dest, long amount) {
+    PbcWriter writer = new PbcWriter();
+    writer.writeU32(0xca76f527L);
+    writer.writeAddress(dest);
+    writer.writeU64(amount);
+    return writer.toBytes();
+  }
+}
@@ -0,0 +1,47 @@
+// Client of the contract TokenContract, generated by pbc-client-generator from its ABI. Do not edit.
+
+import { AbiReader, AbiWriter, Address } from "./pbc-serialization";
+
+export class TokenContractState {
+  constructor(
+    public readonly symbol: Uint8Array,
+    public readonly totalSupply: bigint,
+    public readonly balances: Array<[Address, bigint]>,
+  ) {}
+
+  static read(reader: AbiReader): TokenContractState {
+    const symbol = reader.readByteArray(16);
+    const totalSupply = reader.readU64();
+    const balances = reader.readMap(() => reader.readAddress(), () => reader.readU64());
+    return new TokenContractState(symbol, totalSupply, balances);
+  }
+
+  write(writer: AbiWriter): void {
+    writer.writeByteArray(this.symbol);
+    writer.writeU64(this.totalSupply);
+    writer.writeMap(this.balances, (key0) => writer.writeAddress(key0), (element0) => writer.writeU64(element0));
+  }
+}
+
+/** Reads the state of the contract. */
+export function decodeState(bytes: Uint8Array): TokenContractState {
+  const reader = new AbiReader(bytes);
+  return TokenContractState.read(reader);
+}
+
+/** The RPC invoking the action `mint`. */
+export function mint(amount: bigint): Uint8Array {
+  const writer = new AbiWriter();
+  writer.writeU32(0xbb176fdc);
+  writer.writeU64(amount);
+  return writer.toBytes();
+}
+
+/** The RPC invoking the action `transfer`. */
+export function transfer(dest: Address, amount: bigint): Uint8Array {
+  const writer = new AbiWriter();
+  writer.writeU32(0xca76f527);
+  writer.writeAddress(dest);
+  writer.writeU64(amount);
+  return writer.toBytes();
+}
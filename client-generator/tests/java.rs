@@ -0,0 +1,67 @@
+//! The Java runtime and generated client encode the golden test vectors.
+//!
+//! Skipped when no JDK is installed.
+
+extern crate pbc_client_generator;
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use pbc_client_generator::{java, read_abi, ClientModel};
+
+fn has_jdk() -> bool {
+    Command::new("javac")
+        .arg("-version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[test]
+fn java_client_encodes_golden_vectors() {
+    if !has_jdk() {
+        eprintln!("Skipping the Java client test, as javac is not installed");
+        return;
+    }
+    let dir = std::env::temp_dir().join(format!("pbc-client-generator-{}", std::process::id()));
+    let classes = dir.join("classes");
+    std::fs::create_dir_all(&classes).unwrap();
+
+    let abi = read_abi(include_bytes!("golden/token_contract.abi.json")).unwrap();
+    let model = ClientModel::from_abi(&abi, "TokenContract").unwrap();
+    let mut sources: Vec<PathBuf> = Vec::new();
+    for file in java::generate(&model, None).unwrap() {
+        let path = dir.join(&file.name);
+        std::fs::write(&path, file.contents).unwrap();
+        sources.push(path);
+    }
+    sources.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/java/Vectors.java"));
+
+    let compiled = Command::new("javac")
+        .args(["-encoding", "UTF-8", "-d"])
+        .arg(&classes)
+        .args(&sources)
+        .output()
+        .unwrap();
+    assert!(
+        compiled.status.success(),
+        "{}",
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+    let run = Command::new("java")
+        .arg("-cp")
+        .arg(&classes)
+        .arg("Vectors")
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+
+    // Java has no tuples, so the tuple vector is left to tests/typescript.rs and tests/vectors.rs.
+    let expected: Vec<&str> = include_str!("vectors.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with("tuple "))
+        .collect();
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert_eq!(stdout.lines().collect::<Vec<&str>>(), expected);
+}
@@ -0,0 +1,108 @@
+// Prints the golden test vectors encoded by the Java runtime and the generated token client,
+// after checking that decoding and encoding each of them again gives the same bytes.
+
+import java.util.Arrays;
+import java.util.LinkedHashMap;
+import java.util.LinkedHashSet;
+import java.util.List;
+import java.util.Map;
+import java.util.Optional;
+import java.util.Set;
+import java.util.function.BiConsumer;
+import java.util.function.Function;
+
+public final class Vectors {
+  private static <T> void vector(
+      String name, T value, BiConsumer<PbcWriter, T> write, Function<PbcReader, T> read) {
+    PbcWriter writer = new PbcWriter();
+    write.accept(writer, value);
+    byte[] bytes = writer.toBytes();
+
+    PbcWriter rewriter = new PbcWriter();
+    write.accept(rewriter, read.apply(new PbcReader(bytes)));
+    if (!Arrays.equals(bytes, rewriter.toBytes())) {
+      throw new AssertionError("Decoding and encoding " + name + " changed its bytes");
+    }
+    print(name, bytes);
+  }
+
+  private static void print(String name, byte[] bytes) {
+    StringBuilder hex = new StringBuilder();
+    for (byte b : bytes) {
+      hex.append(String.format("%02x", b));
+    }
+    System.out.println(name + " " + hex);
+  }
+
+  private static byte[] filled(int length, int value) {
+    byte[] bytes = new byte[length];
+    Arrays.fill(bytes, (byte) value);
+    return bytes;
+  }
+
+  public static void main(String[] args) {
+    vector("u32", 42L, PbcWriter::writeU32, PbcReader::readU32);
+    vector("u32_max", 0xffffffffL, PbcWriter::writeU32, PbcReader::readU32);
+    vector("i32_negative", -2, PbcWriter::writeI32, PbcReader::readI32);
+    vector("u64", 0x0102030405060708L, PbcWriter::writeU64, PbcReader::readU64);
+    vector("u64_max", -1L, PbcWriter::writeU64, PbcReader::readU64);
+    vector("i64_negative", -1L, PbcWriter::writeI64, PbcReader::readI64);
+    vector("string", "pbc", PbcWriter::writeString, PbcReader::readString);
+    vector("string_utf8", "\u00e6", PbcWriter::writeString, PbcReader::readString);
+    vector("bytes", new byte[] {1, 2, 3}, PbcWriter::writeBytes, PbcReader::readBytes);
+    vector(
+        "byte_array",
+        new byte[] {1, 2, 3, 4},
+        PbcWriter::writeByteArray,
+        reader -> reader.readByteArray(4));
+    vector(
+        "u64_array",
+        List.of(1L, 2L),
+        (writer, value) -> writer.writeArray(value, writer::writeU64),
+        reader -> reader.readArray(2, reader::readU64));
+    vector(
+        "vec_u64",
+        List.of(5L),
+        (writer, value) -> writer.writeVec(value, writer::writeU64),
+        reader -> reader.readVec(reader::readU64));
+    vector(
+        "option_none",
+        Optional.<Long>empty(),
+        (writer, value) -> writer.writeOption(value, writer::writeU64),
+        reader -> reader.readOption(reader::readU64));
+    vector(
+        "option_some",
+        Optional.of(7L),
+        (writer, value) -> writer.writeOption(value, writer::writeU64),
+        reader -> reader.readOption(reader::readU64));
+    vector(
+        "map",
+        Map.of(1L, 2L),
+        (writer, value) -> writer.writeMap(value, writer::writeU32, writer::writeU64),
+        reader -> reader.readMap(reader::readU32, reader::readU64));
+    vector(
+        "set",
+        new LinkedHashSet<>(List.of(3L, 4L)),
+        (writer, value) -> writer.writeSet(value, writer::writeU64),
+        reader -> reader.readSet(reader::readU64));
+    vector(
+        "address",
+        new Address(Address.PUBLIC_CONTRACT, filled(20, 0x11)),
+        PbcWriter::writeAddress,
+        PbcReader::readAddress);
+
+    byte[] symbol = new byte[16];
+    symbol[0] = 'T';
+    symbol[1] = 'O';
+    symbol[2] = 'K';
+    Map<Address, Long> balances = new LinkedHashMap<>();
+    balances.put(new Address(Address.ACCOUNT, filled(20, 1)), 100L);
+    vector(
+        "token_state",
+        new TokenContract.TokenContractState(symbol, 100L, balances),
+        (writer, value) -> value.write(writer),
+        TokenContract.TokenContractState::read);
+
+    print("transfer_rpc", TokenContract.transfer(new Address(Address.ACCOUNT, filled(20, 2)), 25L));
+  }
+}
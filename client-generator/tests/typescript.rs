@@ -0,0 +1,78 @@
+//! The TypeScript runtime and generated client encode the golden test vectors.
+//!
+//! Skipped when the TypeScript compiler or node is not installed.
+
+extern crate pbc_client_generator;
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use pbc_client_generator::{read_abi, typescript, ClientModel};
+
+fn installed(command: &str) -> bool {
+    Command::new(command)
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[test]
+fn typescript_client_encodes_golden_vectors() {
+    if !installed("tsc") || !installed("node") {
+        eprintln!("Skipping the TypeScript client test, as tsc or node is not installed");
+        return;
+    }
+    let dir = std::env::temp_dir().join(format!(
+        "pbc-client-generator-typescript-{}",
+        std::process::id()
+    ));
+    let out = dir.join("out");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let abi = read_abi(include_bytes!("golden/token_contract.abi.json")).unwrap();
+    let model = ClientModel::from_abi(&abi, "TokenContract").unwrap();
+    let mut sources: Vec<PathBuf> = Vec::new();
+    for file in typescript::generate(&model) {
+        let path = dir.join(&file.name);
+        std::fs::write(&path, file.contents).unwrap();
+        sources.push(path);
+    }
+    let vectors = dir.join("vectors.ts");
+    std::fs::copy(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/typescript/vectors.ts"),
+        &vectors,
+    )
+    .unwrap();
+    sources.push(vectors);
+
+    let compiled = Command::new("tsc")
+        .args(["--strict", "--target", "es2020", "--module", "commonjs"])
+        .arg("--outDir")
+        .arg(&out)
+        .args(&sources)
+        .output()
+        .unwrap();
+    assert!(
+        compiled.status.success(),
+        "{}",
+        String::from_utf8_lossy(&compiled.stdout)
+    );
+    let run = Command::new("node")
+        .arg(out.join("vectors.js"))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+
+    let expected: Vec<&str> = include_str!("vectors.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert_eq!(stdout.lines().collect::<Vec<&str>>(), expected);
+}
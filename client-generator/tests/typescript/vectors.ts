@@ -0,0 +1,132 @@
+// Prints the golden test vectors encoded by the TypeScript runtime and the generated token client,
+// after checking that decoding and encoding each of them again gives the same bytes.
+
+import { AbiReader, AbiWriter, Address, AddressType } from "./pbc-serialization";
+import { TokenContractState, transfer } from "./TokenContract";
+
+function hex(bytes: Uint8Array): string {
+  return Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0")).join("");
+}
+
+function print(name: string, bytes: Uint8Array): void {
+  console.log(`${name} ${hex(bytes)}`);
+}
+
+function vector<T>(
+  name: string,
+  value: T,
+  write: (writer: AbiWriter, value: T) => void,
+  read: (reader: AbiReader) => T,
+): void {
+  const writer = new AbiWriter();
+  write(writer, value);
+  const bytes = writer.toBytes();
+
+  const rewriter = new AbiWriter();
+  write(rewriter, read(new AbiReader(bytes)));
+  if (hex(rewriter.toBytes()) !== hex(bytes)) {
+    throw new Error(`Decoding and encoding ${name} changed its bytes`);
+  }
+  print(name, bytes);
+}
+
+function filled(length: number, value: number): Uint8Array {
+  return new Uint8Array(length).fill(value);
+}
+
+vector("u32", 42, (writer, value) => writer.writeU32(value), (reader) => reader.readU32());
+vector("u32_max", 0xffffffff, (writer, value) => writer.writeU32(value), (reader) => reader.readU32());
+vector("i32_negative", -2, (writer, value) => writer.writeI32(value), (reader) => reader.readI32());
+vector(
+  "u64",
+  0x0102030405060708n,
+  (writer, value) => writer.writeU64(value),
+  (reader) => reader.readU64(),
+);
+vector(
+  "u64_max",
+  0xffffffffffffffffn,
+  (writer, value) => writer.writeU64(value),
+  (reader) => reader.readU64(),
+);
+vector("i64_negative", -1n, (writer, value) => writer.writeI64(value), (reader) => reader.readI64());
+vector("string", "pbc", (writer, value) => writer.writeString(value), (reader) => reader.readString());
+vector(
+  "string_utf8",
+  "æ",
+  (writer, value) => writer.writeString(value),
+  (reader) => reader.readString(),
+);
+vector(
+  "bytes",
+  Uint8Array.of(1, 2, 3),
+  (writer, value) => writer.writeBytes(value),
+  (reader) => reader.readBytes(),
+);
+vector(
+  "byte_array",
+  Uint8Array.of(1, 2, 3, 4),
+  (writer, value) => writer.writeByteArray(value),
+  (reader) => reader.readByteArray(4),
+);
+vector(
+  "u64_array",
+  [1n, 2n],
+  (writer, value) => writer.writeArray(value, (element) => writer.writeU64(element)),
+  (reader) => reader.readArray(2, () => reader.readU64()),
+);
+vector(
+  "vec_u64",
+  [5n],
+  (writer, value) => writer.writeVec(value, (element) => writer.writeU64(element)),
+  (reader) => reader.readVec(() => reader.readU64()),
+);
+vector<bigint | undefined>(
+  "option_none",
+  undefined,
+  (writer, value) => writer.writeOption(value, (inner) => writer.writeU64(inner)),
+  (reader) => reader.readOption(() => reader.readU64()),
+);
+vector<bigint | undefined>(
+  "option_some",
+  7n,
+  (writer, value) => writer.writeOption(value, (inner) => writer.writeU64(inner)),
+  (reader) => reader.readOption(() => reader.readU64()),
+);
+vector<Array<[number, bigint]>>(
+  "map",
+  [[1, 2n]],
+  (writer, value) =>
+    writer.writeMap(value, (key) => writer.writeU32(key), (entry) => writer.writeU64(entry)),
+  (reader) => reader.readMap(() => reader.readU32(), () => reader.readU64()),
+);
+vector(
+  "set",
+  [3n, 4n],
+  (writer, value) => writer.writeSet(value, (element) => writer.writeU64(element)),
+  (reader) => reader.readSet(() => reader.readU64()),
+);
+// Tuples are written and read element by element, as in the generated clients.
+vector<[number, bigint]>(
+  "tuple",
+  [1, -1n],
+  (writer, value) => (writer.writeU32(value[0]), writer.writeI64(value[1])),
+  (reader) => [reader.readU32(), reader.readI64()] as [number, bigint],
+);
+vector(
+  "address",
+  new Address(AddressType.PublicContract, filled(20, 0x11)),
+  (writer, value) => writer.writeAddress(value),
+  (reader) => reader.readAddress(),
+);
+
+const symbol = new Uint8Array(16);
+symbol.set(new TextEncoder().encode("TOK"));
+vector(
+  "token_state",
+  new TokenContractState(symbol, 100n, [[new Address(AddressType.Account, filled(20, 1)), 100n]]),
+  (writer, value) => value.write(writer),
+  (reader) => TokenContractState.read(reader),
+);
+
+print("transfer_rpc", transfer(new Address(AddressType.Account, filled(20, 2)), 25n));
@@ -0,0 +1,81 @@
+//! The golden test vectors agree with the `ReadWrite` implementations of the contracts, so that
+//! clients validated against the vectors agree with the contracts too.
+
+extern crate pbc_client_generator;
+extern crate pbc_contract_common;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use pbc_client_generator::{read_abi, ClientModel};
+use pbc_contract_common::address::Address;
+use pbc_contract_common::serialization::ReadWrite;
+
+fn encode<T: ReadWrite>(value: T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+fn golden_vectors() -> Vec<(String, String)> {
+    include_str!("vectors.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| {
+            let (name, hex) = line.split_once(' ').unwrap();
+            (name.to_string(), hex.to_string())
+        })
+        .collect()
+}
+
+fn transfer_shortname() -> u32 {
+    let abi = read_abi(include_bytes!("golden/token_contract.abi.json")).unwrap();
+    let model = ClientModel::from_abi(&abi, "TokenContract").unwrap();
+    let transfer = model.actions.iter().find(|a| a.name == "transfer").unwrap();
+    transfer.shortname
+}
+
+fn rust_vectors() -> Vec<(&'static str, Vec<u8>)> {
+    let mut symbol = [0u8; 16];
+    symbol[..3].copy_from_slice(b"TOK");
+    let balances = BTreeMap::from([(Address::Account([1u8; 20]), 100u64)]);
+
+    vec![
+        ("u32", encode(42u32)),
+        ("u32_max", encode(u32::MAX)),
+        ("i32_negative", encode(-2i32)),
+        ("u64", encode(0x0102030405060708u64)),
+        ("u64_max", encode(u64::MAX)),
+        ("i64_negative", encode(-1i64)),
+        ("string", encode(String::from("pbc"))),
+        ("string_utf8", encode(String::from("æ"))),
+        ("bytes", encode(vec![1u8, 2, 3])),
+        ("byte_array", encode([1u8, 2, 3, 4])),
+        ("u64_array", encode([1u64, 2])),
+        ("vec_u64", encode(vec![5u64])),
+        ("option_none", encode(None::<u64>)),
+        ("option_some", encode(Some(7u64))),
+        ("map", encode(BTreeMap::from([(1u32, 2u64)]))),
+        ("set", encode(BTreeSet::from([3u64, 4]))),
+        ("tuple", encode((1u32, -1i64))),
+        ("address", encode(Address::PublicContract([0x11; 20]))),
+        // Structs are encoded field by field, as the tuple of their fields.
+        ("token_state", encode((symbol, 100u64, balances))),
+        (
+            "transfer_rpc",
+            encode((transfer_shortname(), Address::Account([2u8; 20]), 25u64)),
+        ),
+    ]
+}
+
+#[test]
+fn golden_vectors_match_the_contract_serialization() {
+    let expected: Vec<(String, String)> = rust_vectors()
+        .into_iter()
+        .map(|(name, bytes)| {
+            let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            (name.to_string(), hex)
+        })
+        .collect();
+
+    assert_eq!(golden_vectors(), expected);
+}
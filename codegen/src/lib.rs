@@ -9,83 +9,1043 @@ extern crate syn;
 
 use proc_macro::TokenStream;
 
-use proc_macro2::Span;
 use quote::ToTokens;
 use sha2::{Digest, Sha256};
 use syn::__private::TokenStream2;
-use syn::{FnArg, Ident, Type, TypePath};
+use syn::spanned::Spanned;
+use syn::{Fields, FnArg, Ident, Type, TypePath};
 
+use registry::{FunctionEntry, FunctionKind, Registry, UpgradeEntry};
+
+mod registry;
+
+/// Declare a contract.
+///
+/// `#[contract] mod name { .. }` expands the `#[state]`, `#[event]`, `#[init]`, `#[action]`,
+/// `#[view]` and `#[upgrade]` items of the module, and generates in it the items depending on
+/// all of them: the `execute` and `view` dispatchers and the ABI exports, and the `client` module
+/// encoding invocations of the contract, which is compiled with the `client` feature of the
+/// contract crate.
+///
+/// Contract functions are either functions of the module, or methods of an `impl` of the state
+/// in the module, which may itself be annotated with `#[contract]`. Actions take the state as
+/// `&mut self` or `self`, and views as `&self`, followed by the `ContractContext` and the RPC
+/// arguments as for functions of the module. The initializer and the upgrade function take no
+/// receiver and return `Self`.
+///
+/// The module must be declared inline and have exactly one `#[state]` struct.
+#[proc_macro_attribute]
+pub fn contract(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    let module: syn::ItemMod = match syn::parse(input.clone()) {
+        Ok(module) => module,
+        Err(_) => return outside_contract_module("contract", input),
+    };
+    if !attrs.is_empty() {
+        let error = syn::Error::new_spanned(
+            TokenStream2::from(attrs),
+            "`#[contract]` does not take any arguments",
+        );
+        return with_error(error, input);
+    }
+
+    match expand_contract(module) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Declare the state of the contract.
+///
+/// The `ReadWrite` implementation is generated from the fields in declaration order, unless
+/// the attribute is given as `#[state(manual_serialization)]`.
+///
+/// `#[state(previous)]` declares the state layout of a previous version of the contract, which
+/// is read by the `#[upgrade]` function, without making it the state of this contract.
+///
+/// Only valid on a struct of a `#[contract]` module.
 #[proc_macro_attribute]
 pub fn state(_attrs: TokenStream, input: TokenStream) -> TokenStream {
-    let state_ast: syn::ItemStruct = syn::parse(input.clone()).unwrap();
-    let original_state_item: proc_macro2::TokenStream = input.into();
-    let state_identifier = state_ast.ident;
-    let _raw_state_name = state_identifier.to_string();
+    outside_contract_module("state", input)
+}
+
+/// Declare an event of the contract, published with `emit`.
+///
+/// Only valid on a struct of a `#[contract]` module.
+#[proc_macro_attribute]
+pub fn event(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+    outside_contract_module("event", input)
+}
+
+/// Declare the initializer of the contract.
+///
+/// Only valid on a function of a `#[contract]` module.
+#[proc_macro_attribute]
+pub fn init(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+    outside_contract_module("init", input)
+}
+
+/// Declare an action of the contract.
+///
+/// `#[action(shortname = 0x01)]` pins the shortname instead of deriving it from the name.
+/// `#[action(only_owner)]` and `#[action(guard = path::to::guard)]` run guards from
+/// `pbc_contract_common::guards` against the context before the action, failing the invocation
+/// with the error of the first guard that fails. The `String` error of the guard is converted
+/// into the error type `E` of an action returning `Result<_, E>`, which must implement
+/// `From<String>`.
+///
+/// Only valid on a function of a `#[contract]` module.
+#[proc_macro_attribute]
+pub fn action(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+    outside_contract_module("action", input)
+}
+
+/// Declare a view of the contract, returning a value without changing the state.
+///
+/// Only valid on a function of a `#[contract]` module.
+#[proc_macro_attribute]
+pub fn view(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+    outside_contract_module("view", input)
+}
+
+/// Migrate the state of the previous version of the contract when it is upgraded to this one.
+///
+/// The function takes the `ContractContext` and the stored state, decoded with the layout of
+/// the previous version, and returns the new contract state or `Result` of it. The previous
+/// layout is usually declared with `#[state(previous)]`. The ABI records both layouts.
+///
+/// Only valid on a function of a `#[contract]` module.
+#[proc_macro_attribute]
+pub fn upgrade(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+    outside_contract_module("upgrade", input)
+}
+
+/// The contract attributes are expanded by the `#[contract]` module declaring them, so reaching
+/// one of them means that the item is not part of a contract module.
+fn outside_contract_module(attribute: &str, input: TokenStream) -> TokenStream {
+    let error = syn::Error::new(
+        proc_macro2::Span::call_site(),
+        format!(
+            "`#[{}]` must be declared inside the contract module, such as `#[contract] mod token {{ .. }}`",
+            attribute
+        ),
+    );
+    with_error(error, input)
+}
+
+/// The attributes expanded by `#[contract]` within the contract module.
+const CONTRACT_ATTRIBUTES: [&str; 7] = [
+    "contract", "state", "event", "init", "action", "view", "upgrade",
+];
 
-    let result = quote! {
-        // TODO [tth]: Can we do this without lazy_static?
-        #[macro_use]
-        extern crate lazy_static;
+fn expand_contract(module: syn::ItemMod) -> syn::Result<TokenStream2> {
+    let syn::ItemMod {
+        attrs,
+        vis,
+        mod_token,
+        ident,
+        content,
+        ..
+    } = module;
+    let items = match content {
+        Some((_, items)) => items,
+        None => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "The contract module must be declared inline, as `#[contract] mod name { .. }`",
+            ))
+        }
+    };
 
-        use reflection::Schema;
+    let state_identifier = find_state(&ident, &items)?;
+    let mut registry = Registry::new(state_identifier.to_string());
+    let mut expanded = TokenStream2::new();
+    for item in items {
+        expanded.extend(expand_item(&mut registry, item));
+    }
+    let exports = generate_exports(&registry, &state_identifier);
 
+    Ok(quote! {
+        #(#attrs)*
+        #vis #mod_token #ident {
+            #expanded
+
+            #exports
+        }
+    })
+}
+
+/// The `#[state]` struct of the contract module, ignoring those declared `#[state(previous)]`.
+///
+/// The state is looked up before any item is expanded, so that contract functions are validated
+/// against it wherever it is declared in the module.
+fn find_state(module: &Ident, items: &[syn::Item]) -> syn::Result<Ident> {
+    let mut state: Option<&Ident> = None;
+    for item in items {
+        let item = match item {
+            syn::Item::Struct(item) => item,
+            _ => continue,
+        };
+        for attr in item.attrs.iter().filter(|attr| attr.path.is_ident("state")) {
+            // Invalid arguments are reported when the struct is expanded.
+            let previous = attribute_arguments(attr)
+                .and_then(parse_state_attributes)
+                .is_ok_and(|attributes| attributes.previous);
+            if previous {
+                continue;
+            }
+            if let Some(state) = state {
+                return Err(syn::Error::new_spanned(
+                    &item.ident,
+                    format!("The contract already has the state `{}`", state),
+                ));
+            }
+            state = Some(&item.ident);
+        }
+    }
+    state.cloned().ok_or_else(|| {
+        syn::Error::new_spanned(
+            module,
+            "The contract module must declare its state with `#[state]`",
+        )
+    })
+}
+
+/// Expand the contract attributes of an item of the contract module, registering what they
+/// declare.
+fn expand_item(registry: &mut Registry, item: syn::Item) -> TokenStream2 {
+    let mut result = TokenStream2::new();
+    match item {
+        syn::Item::Struct(mut item) => {
+            let mut attributes = take_contract_attributes(&mut item.attrs).into_iter();
+            let expansion = match attributes.next() {
+                Some(attr) if attr.path.is_ident("state") => {
+                    attribute_arguments(&attr).and_then(|attrs| expand_state(attrs, &item))
+                }
+                Some(attr) if attr.path.is_ident("event") => Ok(expand_event(registry, &item)),
+                Some(attr) => Err(misplaced(&attr)),
+                None => Ok(item.into_token_stream()),
+            };
+            for attr in attributes {
+                result.extend(
+                    syn::Error::new_spanned(
+                        attr,
+                        "A struct is either the `#[state]` or an `#[event]` of the contract",
+                    )
+                    .to_compile_error(),
+                );
+            }
+            match expansion {
+                Ok(expansion) => result.extend(expansion),
+                Err(error) => result.extend(error.to_compile_error()),
+            }
+        }
+        syn::Item::Fn(mut item) => {
+            for attribute in take_function_attributes(&mut item.attrs) {
+                let expansion =
+                    attribute.and_then(|(expand, attrs)| expand(registry, attrs, &item.sig, None));
+                match expansion {
+                    Ok(expansion) => result.extend(expansion),
+                    Err(error) => result.extend(error.to_compile_error()),
+                }
+            }
+            result.extend(item.into_token_stream());
+        }
+        syn::Item::Impl(item) => result.extend(expand_impl(registry, item)),
+        mut item => {
+            if let Some(attrs) = item_attributes(&mut item) {
+                for attr in take_contract_attributes(attrs) {
+                    result.extend(misplaced(&attr).to_compile_error());
+                }
+            }
+            result.extend(item.into_token_stream());
+        }
+    }
+    result
+}
+
+/// Expand the `#[state]` struct, or a `#[state(previous)]` one.
+fn expand_state(attrs: TokenStream2, state_ast: &syn::ItemStruct) -> syn::Result<TokenStream2> {
+    let attributes = parse_state_attributes(attrs)?;
+    let read_write_impl = if attributes.manual_serialization {
+        TokenStream2::new()
+    } else {
+        generate_read_write(state_ast)
+    };
+
+    let derives = derives();
+    if attributes.previous {
+        return Ok(quote! {
+            #derives
+            #state_ast
+
+            #read_write_impl
+        });
+    }
+
+    Ok(quote! {
         // TODO [tth]: Consider if we should derive PartialEq, Eq and Debug by default.
         //  #[repr(C)] is probably not needed as the struct itself it not passed via FFI.
         #[repr(C)]
-        #[derive(PartialEq, Eq, Debug, Clone, Reflection)]
-        #original_state_item
+        #derives
+        #state_ast
+
+        #read_write_impl
+    })
+}
+
+/// The derives of `#[state]` and `#[event]` structs.
+///
+/// Every path is absolute, and `Reflection` is reached through the `pbc_contract` facade, so
+/// the expansion does not depend on the imports of the contract.
+fn derives() -> TokenStream2 {
+    quote! {
+        #[derive(
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::fmt::Debug,
+            ::core::clone::Clone,
+            ::pbc_contract::Reflection
+        )]
+        #[reflection(crate = "::pbc_contract::reflection")]
+    }
+}
+
+/// The arguments of `#[state(..)]`.
+#[derive(Default)]
+struct StateAttributes {
+    /// Opt out of the generated `ReadWrite`.
+    manual_serialization: bool,
+    /// The struct is the state of a previous version of the contract.
+    previous: bool,
+}
+
+fn parse_state_attributes(attrs: TokenStream2) -> syn::Result<StateAttributes> {
+    let parser = syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated;
+    let arguments = syn::parse::Parser::parse2(parser, attrs)?;
+
+    let mut attributes = StateAttributes::default();
+    for argument in arguments {
+        if argument == "manual_serialization" {
+            attributes.manual_serialization = true;
+        } else if argument == "previous" {
+            attributes.previous = true;
+        } else {
+            return Err(syn::Error::new_spanned(
+                argument,
+                "Unknown argument, expected `manual_serialization` or `previous`",
+            ));
+        }
+    }
+    Ok(attributes)
+}
+
+fn expand_event(registry: &mut Registry, event_ast: &syn::ItemStruct) -> TokenStream2 {
+    let event_identifier = &event_ast.ident;
+    let raw_event_name = event_identifier.to_string();
+    let event_id = hash_identifier(&raw_event_name);
+    registry.register_event(raw_event_name);
+
+    let read_write_impl = generate_read_write(event_ast);
+    let derives = derives();
+
+    quote! {
+        #derives
+        #event_ast
+
+        #read_write_impl
+
+        impl ::pbc_contract::events::Event for #event_identifier {
+            fn event_id() -> u32 {
+                #event_id
+            }
+        }
+    }
+}
+
+/// Emit the items that depend on every `#[state]`, `#[event]` and contract function in the
+/// contract: the `execute` and `view` dispatchers, the ABI export and the `client` module
+/// encoding invocations of the contract.
+fn generate_exports(registry: &Registry, state_identifier: &Ident) -> TokenStream2 {
+    let functions: Vec<TokenStream2> = registry.functions.iter().map(function_abi).collect();
+    let action_shortnames: Vec<u32> = registry
+        .functions
+        .iter()
+        .filter(|entry| entry.kind == FunctionKind::Action)
+        .filter_map(|entry| entry.shortname)
+        .collect();
+    let action_symbols = action_shortnames
+        .iter()
+        .map(|shortname| format_ident!("action_{:x}", shortname));
+    let view_shortnames: Vec<u32> = registry
+        .functions
+        .iter()
+        .filter(|entry| entry.kind == FunctionKind::View)
+        .filter_map(|entry| entry.shortname)
+        .collect();
+    let view_symbols = view_shortnames
+        .iter()
+        .map(|shortname| format_ident!("view_{:x}", shortname));
+    let upgrade = match &registry.upgrade {
+        Some(entry) => {
+            let name = &entry.name;
+            let old_state = syn::parse_str::<Type>(&entry.old_state).unwrap();
+            let error = optional_schema(&entry.error);
+            quote! {
+                Some(UpgradeAbi {
+                    name: #name.to_string(),
+                    old_state: SchemaAbi::of::<#old_state>(),
+                    new_state: SchemaAbi::of::<#state_identifier>(),
+                    error: #error,
+                })
+            }
+        }
+        None => quote! { None },
+    };
+    let client = generate_client(registry, state_identifier);
+    let event_names = registry.events.clone();
+    let event_identifiers: Vec<Ident> = registry
+        .events
+        .iter()
+        .map(|name| format_ident!("{}", name))
+        .collect();
+
+    quote! {
+        #[doc = "Dispatch to the action identified by the shortname at the start of the RPC"]
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn execute(
+            ctx_ptr: *const u8, ctx_len: usize,
+            state_ptr: *const u8, state_len: usize,
+            rpc_ptr: *const u8, rpc_len: usize
+        ) -> *mut u8 {
+            use ::pbc_contract::memory::into_return_descriptor;
+            use ::pbc_contract::result::unknown_shortname_result;
 
-        #[doc = "Export the schema for this contract as json"]
+            if rpc_len < 4 {
+                return into_return_descriptor(unknown_shortname_result(None));
+            }
+            let shortname_bytes = unsafe { std::slice::from_raw_parts(rpc_ptr, 4) };
+            let shortname = u32::from_be_bytes(shortname_bytes.try_into().unwrap());
+            let arguments_ptr = unsafe { rpc_ptr.add(4) };
+            let arguments_len = rpc_len - 4;
+
+            match shortname {
+                #(
+                    #action_shortnames => #action_symbols(
+                        ctx_ptr, ctx_len,
+                        state_ptr, state_len,
+                        arguments_ptr, arguments_len
+                    ),
+                )*
+                _ => into_return_descriptor(unknown_shortname_result(Some(shortname))),
+            }
+        }
+
+        #[doc = "Dispatch to the view identified by the shortname at the start of the RPC"]
         #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn view(
+            ctx_ptr: *const u8, ctx_len: usize,
+            state_ptr: *const u8, state_len: usize,
+            rpc_ptr: *const u8, rpc_len: usize
+        ) -> *mut u8 {
+            use ::pbc_contract::memory::into_return_descriptor;
+            use ::pbc_contract::result::unknown_view_shortname_result;
+
+            if rpc_len < 4 {
+                return into_return_descriptor(unknown_view_shortname_result(None));
+            }
+            let shortname_bytes = unsafe { std::slice::from_raw_parts(rpc_ptr, 4) };
+            let shortname = u32::from_be_bytes(shortname_bytes.try_into().unwrap());
+            let arguments_ptr = unsafe { rpc_ptr.add(4) };
+            let arguments_len = rpc_len - 4;
+
+            match shortname {
+                #(
+                    #view_shortnames => #view_symbols(
+                        ctx_ptr, ctx_len,
+                        state_ptr, state_len,
+                        arguments_ptr, arguments_len
+                    ),
+                )*
+                _ => into_return_descriptor(unknown_view_shortname_result(Some(shortname))),
+            }
+        }
+
+        #[doc = "The ABI of this contract."]
+        pub(crate) fn __contract_abi() -> ::pbc_contract::abi::ContractAbi {
+            use ::pbc_contract::abi::{
+                ArgumentAbi, ContractAbi, EventAbi, FunctionAbi, SchemaAbi, UpgradeAbi,
+            };
+            use ::pbc_contract::events::Event;
+
+            ContractAbi {
+                version: ::pbc_contract::abi::ABI_VERSION,
+                state: SchemaAbi::of::<#state_identifier>(),
+                functions: vec![#(#functions),*],
+                events: vec![#(
+                    EventAbi {
+                        name: #event_names.to_string(),
+                        event_id: <#event_identifiers as Event>::event_id(),
+                        schema: SchemaAbi::of::<#event_identifiers>(),
+                    }
+                ),*],
+                upgrade: #upgrade,
+            }
+        }
+
+        #[doc = "Export the ABI of this contract as json into the buffer at `dst_ptr`."]
+        #[doc = ""]
+        #[doc = "Returns the length of the json, which is only written if it fits in `dst_len`."]
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
         pub extern "C" fn export_contract_schema_json(dst_ptr: *mut u8, dst_len: usize) -> usize {
-            // /*let json = ABI.as_json();
-            // let result = json.as_bytes();
-            // let len = result.len();
-            //
-            // assert!(len <= dst_len, "Buffer too small");
-            //
-            // unsafe { std::ptr::copy(result.as_ptr(), dst_ptr, len) };*/
-            0
+            let json = __contract_abi().as_json();
+            unsafe { ::pbc_contract::memory::copy_to_buffer(json.as_bytes(), dst_ptr, dst_len) }
+        }
+
+        #[doc = "Export the ABI of this contract in the compact binary format into the buffer at `dst_ptr`."]
+        #[doc = ""]
+        #[doc = "Returns the length of the ABI, which is only written if it fits in `dst_len`."]
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn export_contract_schema_binary(dst_ptr: *mut u8, dst_len: usize) -> usize {
+            let binary = __contract_abi().as_binary();
+            unsafe { ::pbc_contract::memory::copy_to_buffer(&binary, dst_ptr, dst_len) }
+        }
+
+        #client
+    }
+}
+
+/// The `client` module for building invocations off-chain, with a function per action returning
+/// its RPC, and `decode_state` reading the state of the contract.
+///
+/// The module is compiled when the contract crate itself enables its `client` feature, so that
+/// the features of other crates depending on the macros do not leak into the contract.
+fn generate_client(registry: &Registry, state_identifier: &Ident) -> TokenStream2 {
+    let actions = registry
+        .functions
+        .iter()
+        .filter(|entry| entry.kind == FunctionKind::Action)
+        .map(|entry| {
+            let fn_identifier = format_ident!("{}", entry.name);
+            let shortname = entry.shortname.unwrap();
+            let docs = format!("The RPC invoking the action `{}`.", entry.name);
+            let names: Vec<Ident> = entry
+                .arguments
+                .iter()
+                .map(|(name, _)| format_ident!("{}", name))
+                .collect();
+            let types: Vec<Type> = entry
+                .arguments
+                .iter()
+                .map(|(_, ty)| syn::parse_str::<Type>(ty).unwrap())
+                .collect();
+            quote! {
+                #[doc = #docs]
+                pub fn #fn_identifier(#(#names: #types),*) -> Vec<u8> {
+                    let mut rpc = #shortname.to_be_bytes().to_vec();
+                    #(<#types as ::pbc_contract::serialization::ReadWrite>::write_to(&#names, &mut rpc).unwrap();)*
+                    rpc
+                }
+            }
+        });
+
+    quote! {
+        #[doc = "Encoding of the invocations of this contract and decoding of its state, for"]
+        #[doc = "clients outside the chain."]
+        #[cfg(feature = "client")]
+        pub mod client {
+            #![allow(unused_imports)]
+            use super::*;
+
+            #(#actions)*
+
+            #[doc = "Read the state of the contract from its serialized form."]
+            pub fn decode_state(mut bytes: &[u8]) -> #state_identifier {
+                <#state_identifier as ::pbc_contract::serialization::ReadWrite>::read_from(&mut bytes)
+            }
         }
+    }
+}
+
+/// Expression building the `FunctionAbi` of a registered function.
+fn function_abi(entry: &FunctionEntry) -> TokenStream2 {
+    let kind = entry.kind;
+    let name = &entry.name;
+    let shortname = match entry.shortname {
+        Some(shortname) => quote! { Some(#shortname) },
+        None => quote! { None },
     };
+    let argument_names = entry.arguments.iter().map(|(name, _)| name);
+    let argument_types = entry
+        .arguments
+        .iter()
+        .map(|(_, ty)| syn::parse_str::<Type>(ty).unwrap());
+    let returns = optional_schema(&entry.returns);
+    let error = optional_schema(&entry.error);
 
-    result.into()
+    quote! {
+        FunctionAbi {
+            kind: #kind,
+            name: #name.to_string(),
+            shortname: #shortname,
+            arguments: vec![#(
+                ArgumentAbi {
+                    name: #argument_names.to_string(),
+                    schema: SchemaAbi::of::<#argument_types>(),
+                }
+            ),*],
+            returns: #returns,
+            error: #error,
+        }
+    }
 }
 
-#[proc_macro_attribute]
-pub fn init(_attrs: TokenStream, input: TokenStream) -> TokenStream {
-    let fn_ast: syn::ItemFn = syn::parse(input.clone()).unwrap();
-    // TODO [tth] validate that the first argument is contract context.
-    // TODO [tth] validate that the return type is "state".
+fn optional_schema(ty: &Option<String>) -> TokenStream2 {
+    match ty {
+        Some(ty) => {
+            let ty = syn::parse_str::<Type>(ty).unwrap();
+            quote! { Some(SchemaAbi::of::<#ty>()) }
+        }
+        None => quote! { None },
+    }
+}
 
-    let (
-        ctx_name,
-        ctx_expression,
-        state_name,
-        state_expression,
-        rpc_param_names,
-        rpc_param_expressions,
-    ) = variables_for_inner_call(&fn_ast, true);
+/// Implement `ReadWrite` for a struct by reading and writing its fields in declaration order.
+fn generate_read_write(item: &syn::ItemStruct) -> TokenStream2 {
+    let identifier = &item.ident;
+    let types: Vec<&Type> = item.fields.iter().map(|field| &field.ty).collect();
+    let (read_body, field_accessors) = match &item.fields {
+        Fields::Named(fields) => {
+            let names: Vec<&Ident> = fields
+                .named
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap())
+                .collect();
+            let read_body = quote! {
+                #identifier {
+                    #(#names: <#types as ::pbc_contract::serialization::ReadWrite>::read_from(reader),)*
+                }
+            };
+            (read_body, names.iter().map(|name| quote! {#name}).collect())
+        }
+        Fields::Unnamed(fields) => {
+            let read_body = quote! {
+                #identifier(
+                    #(<#types as ::pbc_contract::serialization::ReadWrite>::read_from(reader),)*
+                )
+            };
+            let indices = (0..fields.unnamed.len()).map(syn::Index::from);
+            (read_body, indices.map(|index| quote! {#index}).collect())
+        }
+        Fields::Unit => (quote! {#identifier}, Vec::<TokenStream2>::new()),
+    };
+
+    quote! {
+        impl ::pbc_contract::serialization::ReadWrite for #identifier {
+            fn read_from<T: std::io::Read>(reader: &mut T) -> Self {
+                #read_body
+            }
+
+            fn write_to<T: std::io::Write>(&self, writer: &mut T) -> std::io::Result<()> {
+                #(<#types as ::pbc_contract::serialization::ReadWrite>::write_to(&self.#field_accessors, writer)?;)*
+                Ok(())
+            }
+        }
+    }
+}
 
-    let fn_identifier = fn_ast.sig.ident.clone();
+/// Turn the methods of an `impl` of the contract state annotated with `#[init]`, `#[action]`,
+/// `#[view]` or `#[upgrade]` into contract functions.
+///
+/// Other `impl` blocks of the module, such as trait implementations, are kept as they are.
+fn expand_impl(registry: &mut Registry, mut impl_ast: syn::ItemImpl) -> TokenStream2 {
+    let mut result = TokenStream2::new();
+    let marked = !take_contract_attributes(&mut impl_ast.attrs).is_empty();
+    let has_functions = impl_ast.items.iter().any(|item| match item {
+        syn::ImplItem::Method(method) => method.attrs.iter().any(|attr| {
+            CONTRACT_ATTRIBUTES
+                .iter()
+                .any(|name| attr.path.is_ident(name))
+        }),
+        _ => false,
+    });
+    if !marked && !has_functions {
+        return impl_ast.into_token_stream();
+    }
+    let state = &registry.state;
+    if last_segment_name(&impl_ast.self_ty).as_deref() != Some(state.as_str()) {
+        result.extend(
+            syn::Error::new_spanned(
+                &impl_ast.self_ty,
+                format!("Expected an `impl` of the contract state `{}`", state),
+            )
+            .to_compile_error(),
+        );
+        result.extend(impl_ast.into_token_stream());
+        return result;
+    }
+
+    let self_ty = impl_ast.self_ty.as_ref().clone();
+    for item in impl_ast.items.iter_mut() {
+        let method = match item {
+            syn::ImplItem::Method(method) => method,
+            _ => continue,
+        };
+        for attribute in take_function_attributes(&mut method.attrs) {
+            let expansion = attribute
+                .and_then(|(expand, attrs)| expand(registry, attrs, &method.sig, Some(&self_ty)));
+            match expansion {
+                Ok(expansion) => result.extend(expansion),
+                Err(error) => result.extend(error.to_compile_error()),
+            }
+        }
+    }
+
+    result.extend(impl_ast.into_token_stream());
+    result
+}
+
+/// Generates the exported function for a contract function with the given signature, defined
+/// in the `impl` of `self_ty` if any, and registers it.
+type Expander =
+    fn(&mut Registry, TokenStream2, &syn::Signature, Option<&Type>) -> syn::Result<TokenStream2>;
+
+/// Remove the contract attributes from `attrs`, returning them.
+fn take_contract_attributes(attrs: &mut Vec<syn::Attribute>) -> Vec<syn::Attribute> {
+    let (taken, kept) = std::mem::take(attrs).into_iter().partition(|attr| {
+        CONTRACT_ATTRIBUTES
+            .iter()
+            .any(|name| attr.path.is_ident(name))
+    });
+    *attrs = kept;
+    taken
+}
+
+/// Remove the contract attributes from the attributes of a function, returning the expander and
+/// the arguments of each, or the error of an attribute that does not apply to functions.
+fn take_function_attributes(
+    attrs: &mut Vec<syn::Attribute>,
+) -> Vec<syn::Result<(Expander, TokenStream2)>> {
+    take_contract_attributes(attrs)
+        .iter()
+        .map(|attr| {
+            let expand: Expander = if attr.path.is_ident("init") {
+                expand_init
+            } else if attr.path.is_ident("action") {
+                expand_action
+            } else if attr.path.is_ident("view") {
+                expand_view
+            } else if attr.path.is_ident("upgrade") {
+                expand_upgrade
+            } else {
+                return Err(misplaced(attr));
+            };
+            Ok((expand, attribute_arguments(attr)?))
+        })
+        .collect()
+}
+
+/// The error for a contract attribute on an item it does not apply to.
+fn misplaced(attr: &syn::Attribute) -> syn::Error {
+    let name = attr.path.to_token_stream().to_string();
+    let message = match name.as_str() {
+        "state" | "event" => format!("`#[{}]` applies to structs", name),
+        "contract" => String::from(
+            "`#[contract]` applies to the contract module, or an `impl` of the contract state",
+        ),
+        _ => format!("`#[{}]` applies to functions", name),
+    };
+    syn::Error::new_spanned(attr, message)
+}
+
+/// The attributes of an item, for the kinds of items that have any.
+fn item_attributes(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Const(item) => Some(&mut item.attrs),
+        syn::Item::Enum(item) => Some(&mut item.attrs),
+        syn::Item::ExternCrate(item) => Some(&mut item.attrs),
+        syn::Item::ForeignMod(item) => Some(&mut item.attrs),
+        syn::Item::Macro(item) => Some(&mut item.attrs),
+        syn::Item::Mod(item) => Some(&mut item.attrs),
+        syn::Item::Static(item) => Some(&mut item.attrs),
+        syn::Item::Trait(item) => Some(&mut item.attrs),
+        syn::Item::Type(item) => Some(&mut item.attrs),
+        syn::Item::Union(item) => Some(&mut item.attrs),
+        syn::Item::Use(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+/// The tokens inside the parentheses of `#[action(..)]`, which are empty for `#[action]`.
+fn attribute_arguments(attr: &syn::Attribute) -> syn::Result<TokenStream2> {
+    if attr.tokens.is_empty() {
+        return Ok(TokenStream2::new());
+    }
+    let group: proc_macro2::Group = syn::parse2(attr.tokens.clone())?;
+    Ok(group.stream())
+}
+
+fn expand_init(
+    registry: &mut Registry,
+    _attrs: TokenStream2,
+    sig: &syn::Signature,
+    self_ty: Option<&Type>,
+) -> syn::Result<TokenStream2> {
+    let variables = variables_for_inner_call(registry, sig, true, self_ty)?;
+    validate_returns_state(registry, sig, self_ty.is_some())?;
+
+    let fn_identifier = sig.ident.clone();
     let export_symbol = format_ident!("init");
     let raw_fn_name = fn_identifier.to_string();
-    let docs = format!("For contract initializer: {}", raw_fn_name.clone());
+    let docs = format!("For contract initializer: {}", raw_fn_name);
+
+    registry.register_function(FunctionEntry {
+        kind: FunctionKind::Init,
+        name: raw_fn_name,
+        shortname: None,
+        arguments: rpc_arguments(sig, 1),
+        returns: None,
+        error: error_type(sig),
+    });
+
+    Ok(wrap_function_for_export(
+        callee(&fn_identifier, self_ty),
+        export_symbol,
+        docs,
+        return_kind(sig),
+        variables,
+    ))
+}
+
+fn expand_action(
+    registry: &mut Registry,
+    attrs: TokenStream2,
+    sig: &syn::Signature,
+    self_ty: Option<&Type>,
+) -> syn::Result<TokenStream2> {
+    let variables = variables_for_inner_call(registry, sig, false, self_ty)?;
+    let return_kind = match variables.state_passing {
+        StatePassing::MutableReference => {
+            validate_returns_unit(sig)?;
+            match return_kind(sig) {
+                ReturnKind::StateOrError => ReturnKind::MutatedStateOrError,
+                _ => ReturnKind::MutatedState,
+            }
+        }
+        _ => {
+            validate_returns_state(registry, sig, self_ty.is_some())?;
+            return_kind(sig)
+        }
+    };
+
+    let fn_identifier = sig.ident.clone();
+    let raw_fn_name = fn_identifier.to_string();
+    let attributes = parse_action_attributes(attrs)?;
+    let shortname = attributes
+        .shortname
+        .unwrap_or_else(|| hash_identifier(&raw_fn_name));
+    let export_symbol = format_ident!("action_{:x}", shortname);
+    let docs = format!("For contract action: {}", raw_fn_name);
+
+    // Guards fail with a `String`, which is converted into the error of the action, and is the
+    // error of actions not returning `Result`.
+    let error = match error_type(sig) {
+        None if !attributes.guards.is_empty() => Some(String::from("String")),
+        error => error,
+    };
+    let guard_error = match result_error(sig) {
+        Some(error) => error.to_token_stream(),
+        None => quote! { String },
+    };
+    let guards = guard_checks(&attributes.guards, &guard_error);
+    registry.register_function(FunctionEntry {
+        kind: FunctionKind::Action,
+        name: raw_fn_name,
+        shortname: Some(shortname),
+        arguments: rpc_arguments(sig, 2),
+        returns: None,
+        error,
+    });
 
-    let mut result = wrap_function_for_export(
-        fn_identifier,
-        export_symbol.clone(),
+    let variables = InnerCallVariables {
+        guards,
+        ..variables
+    };
+    let mut result = shortname_marker(shortname, &fn_identifier);
+    result.extend(wrap_function_for_export(
+        callee(&fn_identifier, self_ty),
+        export_symbol,
         docs,
-        ctx_name,
+        return_kind,
+        variables,
+    ));
+    Ok(result)
+}
+
+/// Statements running `guards` against the context in order, failing the invocation with the
+/// error of the first guard that fails, converted into `error` with `From<String>`.
+fn guard_checks(guards: &[syn::Path], error: &TokenStream2) -> Vec<TokenStream2> {
+    guards
+        .iter()
+        .map(|guard| {
+            quote_spanned! {guard.span()=>
+                if let Err(error) = #guard(&context) {
+                    #[allow(clippy::useless_conversion)]
+                    let error = <#error as ::core::convert::From<String>>::from(error);
+                    return ::pbc_contract::memory::into_return_descriptor(
+                        ::pbc_contract::result::err_result(&error),
+                    );
+                }
+            }
+        })
+        .collect()
+}
+
+fn expand_view(
+    registry: &mut Registry,
+    attrs: TokenStream2,
+    sig: &syn::Signature,
+    self_ty: Option<&Type>,
+) -> syn::Result<TokenStream2> {
+    let variables = variables_for_inner_call(registry, sig, false, self_ty)?;
+
+    let fn_identifier = sig.ident.clone();
+    let raw_fn_name = fn_identifier.to_string();
+    let shortname = parse_shortname(attrs, &raw_fn_name)?;
+    let export_symbol = format_ident!("view_{:x}", shortname);
+    let docs = format!("For contract view: {}", raw_fn_name);
+
+    let returns = match &sig.output {
+        syn::ReturnType::Type(_, ty) => Some(ty.to_token_stream().to_string()),
+        syn::ReturnType::Default => {
+            return Err(syn::Error::new_spanned(sig, "Views must return a value"));
+        }
+    };
+    registry.register_function(FunctionEntry {
+        kind: FunctionKind::View,
+        name: raw_fn_name,
+        shortname: Some(shortname),
+        arguments: rpc_arguments(sig, 2),
+        returns,
+        error: None,
+    });
+
+    let mut result = shortname_marker(shortname, &fn_identifier);
+    result.extend(wrap_function_for_export(
+        callee(&fn_identifier, self_ty),
+        export_symbol,
+        docs,
+        ReturnKind::Value,
+        variables,
+    ));
+    Ok(result)
+}
+
+fn expand_upgrade(
+    registry: &mut Registry,
+    attrs: TokenStream2,
+    sig: &syn::Signature,
+    self_ty: Option<&Type>,
+) -> syn::Result<TokenStream2> {
+    if !attrs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            attrs,
+            "`#[upgrade]` does not take any arguments",
+        ));
+    }
+
+    let mut iter = sig.inputs.iter();
+    let ctx_argument = iter.next().ok_or_else(|| {
+        syn::Error::new_spanned(
+            sig,
+            "Upgrade functions must take `ContractContext` as their first argument",
+        )
+    })?;
+    validate_argument_type(ctx_argument, "ContractContext")?;
+    let (_, ctx_expression) =
+        extract_name_and_expression(format_ident!("ctx_reader"), ctx_argument)?;
+
+    let old_state_argument = iter.next().ok_or_else(|| {
+        syn::Error::new_spanned(
+            sig,
+            "Upgrade functions must take the previous state as their second argument",
+        )
+    })?;
+    let old_state = match split_state_argument(old_state_argument)? {
+        (StatePassing::Value, old_state) => old_state,
+        (_, old_state) => {
+            return Err(syn::Error::new_spanned(
+                old_state,
+                "Upgrade functions must take the previous state by value",
+            ))
+        }
+    };
+    if let Some(extra) = iter.next() {
+        return Err(syn::Error::new_spanned(
+            extra,
+            "Upgrade functions take only the context and the previous state",
+        ));
+    }
+    validate_returns_state(registry, sig, self_ty.is_some())?;
+
+    let fn_identifier = sig.ident.clone();
+    let raw_fn_name = fn_identifier.to_string();
+    let docs = format!("For contract upgrade: {}", raw_fn_name);
+
+    registry
+        .register_upgrade(UpgradeEntry {
+            name: raw_fn_name,
+            old_state: old_state.to_token_stream().to_string(),
+            error: error_type(sig),
+        })
+        .map_err(|message| syn::Error::new(fn_identifier.span(), message))?;
+
+    let variables = InnerCallVariables {
         ctx_expression,
-        state_name,
-        state_expression,
-        rpc_param_names,
-        rpc_param_expressions,
+        state_expression: Some(quote! {
+            <#old_state as ::pbc_contract::serialization::ReadWrite>::read_from(&mut state_reader);
+        }),
+        state_passing: StatePassing::Value,
+        receiver: false,
+        rpc_param_names: vec![],
+        rpc_param_decodings: vec![],
+        guards: vec![],
+    };
+    Ok(wrap_function_for_export(
+        callee(&fn_identifier, self_ty),
+        format_ident!("upgrade"),
+        docs,
+        return_kind(sig),
+        variables,
+    ))
+}
+
+/// A `const` named after the shortname of an action or view.
+///
+/// Shortnames are unique across actions and views, so that a shortname identifies a function of
+/// the contract without knowing its kind. Two functions with the same shortname define the same
+/// constant, which fails to compile with E0428 at the name of the second function.
+fn shortname_marker(shortname: u32, fn_identifier: &Ident) -> TokenStream2 {
+    let marker = Ident::new(
+        &format!("__PBC_SHORTNAME_{:08X}", shortname),
+        fn_identifier.span(),
     );
+    quote_spanned! {fn_identifier.span()=>
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        const #marker: () = ();
+    }
+}
 
-    result.extend(TokenStream2::from(input));
-    result.into()
+/// The path calling a contract function, which is a method of `self_ty` if given.
+fn callee(fn_identifier: &Ident, self_ty: Option<&Type>) -> TokenStream2 {
+    match self_ty {
+        Some(self_ty) => quote! { <#self_ty>::#fn_identifier },
+        None => quote! { #fn_identifier },
+    }
 }
 
 fn hash_identifier(raw_name: &String) -> u32 {
@@ -93,241 +1053,615 @@ fn hash_identifier(raw_name: &String) -> u32 {
     Digest::update(&mut digest, raw_name.as_bytes());
     let output = digest.finalize();
     let last_four_bytes = output.chunks(4).next().unwrap();
-    let new_function_identifier = u32::from_le_bytes(last_four_bytes.try_into().unwrap());
-    new_function_identifier
+    u32::from_le_bytes(last_four_bytes.try_into().unwrap())
 }
 
-#[proc_macro_attribute]
-pub fn action(_attrs: TokenStream, input: TokenStream) -> TokenStream {
-    let fn_ast: syn::ItemFn = syn::parse(input.clone()).unwrap();
-    // TODO [jm] Validate arguments
+/// The shortname given by `shortname = 0x..` in the attribute, or else the hash of the name.
+fn parse_shortname(attrs: TokenStream2, raw_fn_name: &String) -> syn::Result<u32> {
+    let parser =
+        syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated;
+    let arguments = syn::parse::Parser::parse2(parser, attrs)?;
 
-    let (
-        ctx_name,
-        ctx_expression,
-        state_name,
-        state_expression,
-        rpc_param_names,
-        rpc_param_expressions,
-    ) = variables_for_inner_call(&fn_ast, false);
+    let mut shortname = None;
+    for argument in arguments {
+        if !argument.path.is_ident("shortname") {
+            return Err(syn::Error::new_spanned(
+                argument.path,
+                "Unknown argument, expected `shortname`",
+            ));
+        }
+        match argument.lit {
+            syn::Lit::Int(value) => shortname = Some(value.base10_parse::<u32>()?),
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "The shortname must be an integer literal, such as 0x01",
+                ))
+            }
+        }
+    }
 
-    let fn_identifier = fn_ast.sig.ident.clone();
+    Ok(shortname.unwrap_or_else(|| hash_identifier(raw_fn_name)))
+}
 
-    let fn_name_hash = hash_identifier(&fn_identifier.to_string());
-    let export_symbol = format_ident!("action_{:x}", fn_name_hash);
-    let raw_fn_name = fn_identifier.to_string();
-    let docs = format!("For contract action: {}", raw_fn_name.clone());
+/// The arguments of `#[action(..)]`.
+struct ActionAttributes {
+    shortname: Option<u32>,
+    /// Paths of the guards to run against the context before the action, in order.
+    guards: Vec<syn::Path>,
+}
 
-    let mut result = wrap_function_for_export(
-        fn_identifier,
-        export_symbol.clone(),
-        docs,
-        ctx_name,
-        ctx_expression,
-        state_name,
-        state_expression,
-        rpc_param_names,
-        rpc_param_expressions,
-    );
+/// A single argument of `#[action(..)]`.
+enum ActionArgument {
+    Shortname(u32),
+    OnlyOwner,
+    Guard(syn::Path),
+}
+
+impl syn::parse::Parse for ActionArgument {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        if name == "only_owner" {
+            Ok(ActionArgument::OnlyOwner)
+        } else if name == "shortname" {
+            input.parse::<syn::Token![=]>()?;
+            match input.parse::<syn::Lit>()? {
+                syn::Lit::Int(value) => Ok(ActionArgument::Shortname(value.base10_parse()?)),
+                other => Err(syn::Error::new_spanned(
+                    other,
+                    "The shortname must be an integer literal, such as 0x01",
+                )),
+            }
+        } else if name == "guard" {
+            input.parse::<syn::Token![=]>()?;
+            Ok(ActionArgument::Guard(input.parse()?))
+        } else {
+            Err(syn::Error::new_spanned(
+                name,
+                "Unknown argument, expected `shortname`, `only_owner` or `guard`",
+            ))
+        }
+    }
+}
 
+fn parse_action_attributes(attrs: TokenStream2) -> syn::Result<ActionAttributes> {
+    let parser = syn::punctuated::Punctuated::<ActionArgument, syn::Token![,]>::parse_terminated;
+    let arguments = syn::parse::Parser::parse2(parser, attrs)?;
+
+    let mut attributes = ActionAttributes {
+        shortname: None,
+        guards: Vec::new(),
+    };
+    for argument in arguments {
+        match argument {
+            ActionArgument::Shortname(shortname) => attributes.shortname = Some(shortname),
+            ActionArgument::OnlyOwner => attributes
+                .guards
+                .push(syn::parse_quote! { ::pbc_contract::guards::only_owner }),
+            ActionArgument::Guard(guard) => attributes.guards.push(guard),
+        }
+    }
+    Ok(attributes)
+}
+
+/// Report `error` while keeping the annotated item, so that it can still be referenced.
+fn with_error(error: syn::Error, input: TokenStream) -> TokenStream {
+    let mut result = error.to_compile_error();
     result.extend(TokenStream2::from(input));
     result.into()
 }
 
+/// Names and types, as source text, of the arguments following the first `skip` arguments.
+fn rpc_arguments(signature: &syn::Signature, skip: usize) -> Vec<(String, String)> {
+    signature
+        .inputs
+        .iter()
+        .skip(skip)
+        .filter_map(|argument| match argument {
+            FnArg::Typed(pat) => {
+                let name = match pat.pat.as_ref() {
+                    syn::Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+                    other => other.to_token_stream().to_string(),
+                };
+                Some((name, decoded_type(&pat.ty).to_token_stream().to_string()))
+            }
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+/// The owned type with the same encoding as an argument of type `ty`, as listed in the ABI.
+fn decoded_type(ty: &Type) -> Type {
+    match ty {
+        Type::Reference(reference) => match reference.elem.as_ref() {
+            elem if is_ident(elem, "str") => syn::parse_quote! { String },
+            Type::Slice(slice) => {
+                let elem = &slice.elem;
+                syn::parse_quote! { Vec<#elem> }
+            }
+            elem => elem.clone(),
+        },
+        ty => ty.clone(),
+    }
+}
+
+/// The error type `E`, as source text, of a function returning `Result<State, E>`.
+fn error_type(signature: &syn::Signature) -> Option<String> {
+    result_error(signature).map(|error| error.to_token_stream().to_string())
+}
+
+/// The error `E` of a function returning `Result<State, E>`.
+fn result_error(signature: &syn::Signature) -> Option<&syn::GenericArgument> {
+    if let syn::ReturnType::Type(_, ty) = &signature.output {
+        if let Type::Path(path) = ty.as_ref() {
+            let last_segment = path.path.segments.last().unwrap();
+            if let syn::PathArguments::AngleBracketed(arguments) = &last_segment.arguments {
+                if last_segment.ident == "Result" {
+                    return arguments.args.iter().nth(1);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// How the value returned by a contract function becomes the result of the invocation.
+enum ReturnKind {
+    /// The function returns the new state.
+    State,
+    /// The function returns `Result<State, E>`, failing the invocation on `Err`.
+    StateOrError,
+    /// The function is a view returning a value, and leaves the state unchanged.
+    Value,
+    /// The function mutates the state through `&mut` and returns nothing.
+    MutatedState,
+    /// The function mutates the state through `&mut` and returns `Result<(), E>`, failing the
+    /// invocation on `Err`.
+    MutatedStateOrError,
+}
+
+fn return_kind(signature: &syn::Signature) -> ReturnKind {
+    if let syn::ReturnType::Type(_, ty) = &signature.output {
+        if let Type::Path(path) = ty.as_ref() {
+            let last_segment = path.path.segments.last().unwrap();
+            if last_segment.ident == "Result" {
+                return ReturnKind::StateOrError;
+            }
+        }
+    }
+    ReturnKind::State
+}
+
+/// Names and deserializing expressions for the arguments of a contract function.
+struct InnerCallVariables {
+    ctx_expression: TokenStream2,
+    state_expression: Option<TokenStream2>,
+    state_passing: StatePassing,
+    /// Whether the state is passed as the receiver of a method, ahead of the context.
+    receiver: bool,
+    rpc_param_names: Vec<TokenStream2>,
+    rpc_param_decodings: Vec<TokenStream2>,
+    /// Statements checking the context with the guards before the call, see `guard_checks`.
+    guards: Vec<TokenStream2>,
+}
+
+/// How the decoded state is handed to a contract function.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StatePassing {
+    /// `state: State`
+    Value,
+    /// `state: &State`
+    Reference,
+    /// `state: &mut State`, where the mutated state becomes the new state.
+    MutableReference,
+}
+
+/// Generate the exported function calling `fn_identifier`, following the buffer ownership
+/// protocol of `pbc_contract_common::memory`.
 fn wrap_function_for_export(
-    fn_identifier: Ident,
+    callee: TokenStream2,
     export_symbol: Ident,
     docs: String,
-    _ctx_name: TokenStream2,
-    ctx_expression: TokenStream2,
-    state_name: Option<TokenStream2>,
-    state_expression: Option<TokenStream2>,
-    rpc_parameter_names: Vec<TokenStream2>,
-    rpc_parameter_expressions: Vec<TokenStream2>,
+    return_kind: ReturnKind,
+    variables: InnerCallVariables,
 ) -> TokenStream2 {
-    if state_name.is_some() && state_expression.is_some() {
-        quote! {
-            #[doc = #docs]
-            #[no_mangle]
-            pub extern "C" fn #export_symbol(
-                ctx_ptr: *const u8, ctx_len: usize,
-                state_ptr: *const u8, state_len: usize,
-                rpc_ptr: *const u8, rpc_len: usize
-            ) -> i64 {
-                use std::io::Cursor;
-
-                let rpc = unsafe { std::slice::from_raw_parts(rpc_ptr, rpc_len as usize).to_owned() };
-                let mut rpc_reader = Cursor::new(rpc);
-
-                // The expressions, which are used to evaluate the arguments for the inner function,
-                // deserialize from "cursor" meaning they have side effects.
-                // Because of this, we need to ensure that they are evaluated in the correct order,
-                // thus we will bind them to variables instead of #fn_identifier(#(#expression),*)
-                // (since function arguments are not guaranteed to evaluate left to right).
-                #(let #rpc_parameter_names = #rpc_parameter_expressions)*
-
-
-                let ctx = unsafe { std::slice::from_raw_parts(ctx_ptr, ctx_len as usize).to_owned() };
-                let mut ctx_reader = Cursor::new(ctx);
+    let InnerCallVariables {
+        ctx_expression,
+        state_expression,
+        state_passing,
+        receiver,
+        rpc_param_names,
+        rpc_param_decodings,
+        guards,
+    } = variables;
 
+    let (state_binding, state_argument) = match state_passing {
+        StatePassing::Value => (quote! { prev_state }, quote! { prev_state, }),
+        StatePassing::Reference => (quote! { prev_state }, quote! { &prev_state, }),
+        StatePassing::MutableReference => (quote! { mut prev_state }, quote! { &mut prev_state, }),
+    };
+    let (state_parameters, state_reading, state_argument) = match state_expression {
+        Some(state_expression) => (
+            quote! { state_ptr: *const u8, state_len: usize, },
+            quote! {
                 let state_slice = unsafe { std::slice::from_raw_parts(state_ptr, state_len as usize).to_owned() };
                 let mut state_reader = Cursor::new(state_slice);
+                let #state_binding = #state_expression;
+            },
+            state_argument,
+        ),
+        None => (quote! {}, quote! {}, quote! {}),
+    };
 
-                let context = #ctx_expression;
-                let prev_state = #state_expression;
-
-                let state = #fn_identifier(context, prev_state, #(#rpc_parameter_names),*);
-
-                let mut method_result: Vec<u8> = Vec::new();
-                state.write_to(&mut method_result).unwrap();
-
-                let len = method_result.len() as i64;
-                let ptr = method_result.as_ptr() as i64;
-                std::mem::forget(method_result);
-
-                len << 32 | ptr
-            }
-        }
+    let call = if receiver {
+        quote! { #callee(#state_argument context, #(#rpc_param_names),*) }
     } else {
-        quote! {
-            #[doc = #docs]
-            #[no_mangle]
-            pub extern "C" fn #export_symbol(
-                ctx_ptr: *const u8, ctx_len: usize,
-                rpc_ptr: *const u8, rpc_len: usize
-            ) -> i64 {
-                use std::io::Cursor;
+        quote! { #callee(context, #state_argument #(#rpc_param_names),*) }
+    };
 
-                let rpc = unsafe { std::slice::from_raw_parts(rpc_ptr, rpc_len as usize).to_owned() };
-                let mut rpc_reader = Cursor::new(rpc);
+    let result_writing = match return_kind {
+        ReturnKind::State => quote! {
+            ::pbc_contract::result::ok_result(&result)
+        },
+        ReturnKind::StateOrError => quote! {
+            match result {
+                Ok(state) => ::pbc_contract::result::ok_result(&state),
+                Err(error) => ::pbc_contract::result::err_result(&error),
+            }
+        },
+        ReturnKind::Value => quote! {
+            ::pbc_contract::result::value_result(&result)
+        },
+        ReturnKind::MutatedState => quote! {
+            ::pbc_contract::result::ok_result(&prev_state)
+        },
+        ReturnKind::MutatedStateOrError => quote! {
+            match result {
+                Ok(()) => ::pbc_contract::result::ok_result(&prev_state),
+                Err(error) => ::pbc_contract::result::err_result(&error),
+            }
+        },
+    };
 
-                // The expressions, which are used to evaluate the arguments for the inner function,
-                // deserialize from "cursor" meaning they have side effects.
-                // Because of this, we need to ensure that they are evaluated in the correct order,
-                // thus we will bind them to variables instead of #fn_identifier(#(#expression),*)
-                // (since function arguments are not guaranteed to evaluate left to right).
-                #(let #rpc_parameter_names = #rpc_parameter_expressions)*
+    quote! {
+        #[doc = #docs]
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn #export_symbol(
+            ctx_ptr: *const u8, ctx_len: usize,
+            #state_parameters
+            rpc_ptr: *const u8, rpc_len: usize
+        ) -> *mut u8 {
+            use std::io::Cursor;
 
+            // Reference arguments borrow from the RPC buffer, which outlives the inner call.
+            let mut rpc_reader: &[u8] = unsafe { std::slice::from_raw_parts(rpc_ptr, rpc_len as usize) };
 
-                let ctx = unsafe { std::slice::from_raw_parts(ctx_ptr, ctx_len as usize).to_owned() };
-                let mut ctx_reader = Cursor::new(ctx);
+            // The expressions, which are used to evaluate the arguments for the inner function,
+            // deserialize from the reader meaning they have side effects.
+            // Because of this, we need to ensure that they are evaluated in the correct order,
+            // thus we will bind them to variables instead of #callee(#(#expression),*)
+            // (since function arguments are not guaranteed to evaluate left to right).
+            #(#rpc_param_decodings)*
 
-                let context = #ctx_expression;
 
-                let state = #fn_identifier(context,  #(#rpc_parameter_names),*);
+            let ctx = unsafe { std::slice::from_raw_parts(ctx_ptr, ctx_len as usize).to_owned() };
+            let mut ctx_reader = Cursor::new(ctx);
 
-                let mut method_result: Vec<u8> = Vec::new();
-                state.write_to(&mut method_result).unwrap();
+            let context = #ctx_expression;
+            #(#guards)*
+            #state_reading
 
-                let len = method_result.len() as i64;
-                let ptr = method_result.as_ptr() as i64;
-                std::mem::forget(method_result);
+            let result = #call;
+            let method_result: Vec<u8> = #result_writing;
 
-                len << 32 | ptr
-            }
+            ::pbc_contract::memory::into_return_descriptor(method_result)
         }
     }
 }
 
+/// The variables for calling a contract function with signature `sig`, which is a method of
+/// `self_ty` if given.
 fn variables_for_inner_call(
-    item: &syn::ItemFn,
+    registry: &Registry,
+    sig: &syn::Signature,
     is_init: bool,
-) -> (
-    TokenStream2,
-    TokenStream2,
-    Option<TokenStream2>,
-    Option<TokenStream2>,
-    Vec<TokenStream2>,
-    Vec<TokenStream2>,
-) {
-    let mut var_name: Vec<TokenStream2> = Vec::new();
-    let mut expression: Vec<TokenStream2> = Vec::new();
-
-    let mut iter = item.sig.inputs.iter();
-
-    let (ctx_name, ctx_expression) =
-        extract_name_and_expression(format_ident!("ctx_reader"), iter.next().unwrap());
-    let (state_name, state_expression) = if is_init {
-        (None, None)
+    self_ty: Option<&Type>,
+) -> syn::Result<InnerCallVariables> {
+    let mut rpc_param_names: Vec<TokenStream2> = Vec::new();
+    let mut rpc_param_decodings: Vec<TokenStream2> = Vec::new();
+
+    let mut iter = sig.inputs.iter();
+
+    // Methods receive the state as `self`, ahead of the context.
+    let receiver = match self_ty {
+        Some(self_ty) if !is_init => match iter.next() {
+            Some(FnArg::Receiver(receiver)) => {
+                let passing = match (&receiver.reference, &receiver.mutability) {
+                    (None, _) => StatePassing::Value,
+                    (Some(_), None) => StatePassing::Reference,
+                    (Some(_), Some(_)) => StatePassing::MutableReference,
+                };
+                Some((passing, self_ty))
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    sig,
+                    "Actions and views in a `#[contract]` impl must take `self`, `&self` or `&mut self`",
+                ))
+            }
+        },
+        _ => None,
+    };
+
+    let ctx_argument = iter.next().ok_or_else(|| {
+        syn::Error::new_spanned(
+            sig,
+            "Contract functions must take `ContractContext` as their first argument",
+        )
+    })?;
+    validate_argument_type(ctx_argument, "ContractContext")?;
+    let (_, ctx_expression) =
+        extract_name_and_expression(format_ident!("ctx_reader"), ctx_argument)?;
+
+    let mut state_passing = StatePassing::Value;
+    let state_expression = if is_init {
+        None
+    } else if let Some((passing, state_type)) = receiver {
+        state_passing = passing;
+        Some(quote! {
+            <#state_type as ::pbc_contract::serialization::ReadWrite>::read_from(&mut state_reader);
+        })
     } else {
-        let (a, b) =
-            extract_name_and_expression(format_ident!("state_reader"), iter.next().unwrap());
-        (Some(a), Some(b))
+        let state_argument = iter.next().ok_or_else(|| {
+            syn::Error::new_spanned(
+                sig,
+                "Actions and views must take the contract state as their second argument",
+            )
+        })?;
+        let (passing, state_type) = split_state_argument(state_argument)?;
+        let state = &registry.state;
+        if last_segment_name(state_type).as_deref() != Some(state.as_str()) {
+            return Err(syn::Error::new_spanned(
+                state_type,
+                format!("Expected the contract state `{}`", state),
+            ));
+        }
+        state_passing = passing;
+        Some(quote! {
+            <#state_type as ::pbc_contract::serialization::ReadWrite>::read_from(&mut state_reader);
+        })
     };
 
     for token in iter {
         let reader_ident = format_ident!("rpc_reader");
-        let (name, expr) = extract_name_and_expression(reader_ident, token);
-        var_name.push(name);
-        expression.push(expr);
+        let (name, decoding) = extract_name_and_decoding(reader_ident, token)?;
+        rpc_param_names.push(name);
+        rpc_param_decodings.push(decoding);
     }
 
-    (
-        ctx_name,
+    Ok(InnerCallVariables {
         ctx_expression,
-        state_name,
         state_expression,
-        var_name,
-        expression,
-    )
+        state_passing,
+        receiver: receiver.is_some(),
+        rpc_param_names,
+        rpc_param_decodings,
+        guards: Vec::new(),
+    })
 }
 
-fn extract_name_and_expression(reader_ident: Ident, token: &FnArg) -> (TokenStream2, TokenStream2) {
-    match token {
-        FnArg::Receiver(_) => {
-            // TODO [tth]: Note that self receivers with a specified type,
-            //  such as self: Box<Self>, are parsed as a FnArg::Typed.
-            panic!("Contract functions cannot be part of an `impl`.")
-        }
-        FnArg::Typed(pat) => {
-            let name = pat.pat.to_token_stream().to_string();
-            let variable_identifier = format_ident!("tmp_{}", name);
-            let var_name = quote! {#variable_identifier};
-            let ty = *(pat.ty.clone());
-            match ty {
-                Type::Path(path) => {
-                    let expr = generate_instantiating_expression(reader_ident, path);
-                    (var_name, expr)
-                }
-                Type::Tuple(_) => {
-                    panic!("Unsupported tuple type");
-                }
-                Type::Array(_) => {
-                    panic!("Unsupported array type");
-                }
-                Type::ImplTrait(_) => {
-                    panic!("Unsupported impl trait type");
-                }
-                Type::Reference(_) => {
-                    panic!("Unsupported reference type");
-                }
-                Type::Slice(_) => {
-                    panic!("Unsupported slice type");
-                }
-                _ => {
-                    panic!("Unsupported argument type")
-                }
-            }
+/// The last path segment of `ty`, such as `ContractContext` for
+/// `pbc_contract_common::context::ContractContext`.
+fn last_segment_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// How the state argument is passed, and the type of the state itself.
+fn split_state_argument(argument: &FnArg) -> syn::Result<(StatePassing, &Type)> {
+    let pat = typed_argument(argument)?;
+    Ok(match pat.ty.as_ref() {
+        Type::Reference(reference) if reference.mutability.is_some() => {
+            (StatePassing::MutableReference, reference.elem.as_ref())
+        }
+        Type::Reference(reference) => (StatePassing::Reference, reference.elem.as_ref()),
+        ty => (StatePassing::Value, ty),
+    })
+}
+
+/// Fail unless `argument` is of the type named `expected`.
+fn validate_argument_type(argument: &FnArg, expected: &str) -> syn::Result<()> {
+    if let FnArg::Typed(pat) = argument {
+        if last_segment_name(&pat.ty).as_deref() != Some(expected) {
+            return Err(syn::Error::new_spanned(
+                &pat.ty,
+                format!("Expected `{}`", expected),
+            ));
         }
     }
+    Ok(())
 }
 
-fn generate_instantiating_expression(reader_ident: Ident, path: TypePath) -> TokenStream2 {
-    match path.path.get_ident() {
-        Some(ident) => {
-            if ident.eq(&Ident::new("u64", Span::call_site())) {
-                quote! {#reader_ident.read_u64_be();}
-            } else if ident.eq(&Ident::new("i64", Span::call_site())) {
-                quote! {#reader_ident.read_i64_be();}
-            } else if ident.eq(&Ident::new("i32", Span::call_site())) {
-                quote! {#reader_ident.read_i32_be();}
-            } else {
-                quote! {<#ident>::read_from(&mut #reader_ident);}
+/// Fail unless a function mutating the state through `&mut` returns nothing, or `Result<(), E>`.
+fn validate_returns_unit(signature: &syn::Signature) -> syn::Result<()> {
+    let ty = match &signature.output {
+        syn::ReturnType::Type(_, ty) => ty,
+        syn::ReturnType::Default => return Ok(()),
+    };
+    let returned = match return_kind(signature) {
+        ReturnKind::StateOrError => result_ok_type(ty),
+        _ => Some(ty.as_ref()),
+    };
+    match returned {
+        Some(Type::Tuple(tuple)) if tuple.elems.is_empty() => Ok(()),
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "Actions taking the state as `&mut` must return nothing, or `Result<(), E>`",
+        )),
+    }
+}
+
+/// Fail unless the function returns the contract state, or `Result` of the contract state.
+///
+/// Methods of the state may return `Self` instead.
+fn validate_returns_state(
+    registry: &Registry,
+    signature: &syn::Signature,
+    is_method: bool,
+) -> syn::Result<()> {
+    let ty = match &signature.output {
+        syn::ReturnType::Type(_, ty) => ty,
+        syn::ReturnType::Default => {
+            return Err(syn::Error::new_spanned(
+                signature,
+                "Contract functions must return the contract state, or `Result` of it",
+            ))
+        }
+    };
+    let state = &registry.state;
+
+    let returned = match return_kind(signature) {
+        ReturnKind::StateOrError => result_ok_type(ty),
+        _ => Some(ty.as_ref()),
+    };
+    let returned = returned.and_then(last_segment_name);
+    let returns_self = is_method && returned.as_deref() == Some("Self");
+    if !returns_self && returned.as_deref() != Some(state.as_str()) {
+        return Err(syn::Error::new_spanned(
+            ty,
+            format!(
+                "Expected the contract state `{}`, or `Result<{}, E>`",
+                state, state
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// The type `T` of `Result<T, E>`.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(path) = ty {
+        let last_segment = path.path.segments.last()?;
+        if let syn::PathArguments::AngleBracketed(arguments) = &last_segment.arguments {
+            if let Some(syn::GenericArgument::Type(ok)) = arguments.args.first() {
+                return Some(ok);
             }
         }
-        None => {
-            let tokens = path.path.segments.into_token_stream();
-            quote! {<#tokens>::read_from(&mut #reader_ident);}
+    }
+    None
+}
+
+fn extract_name_and_expression(
+    reader_ident: Ident,
+    token: &FnArg,
+) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let pat = typed_argument(token)?;
+    let variable_identifier = format_ident!("tmp_{}", argument_name(pat)?);
+    let expr = instantiating_expression(reader_ident, &pat.ty)?;
+    Ok((quote! {#variable_identifier}, quote! {#expr;}))
+}
+
+/// The variable holding an RPC argument, and the statements decoding it from `reader_ident`.
+///
+/// `&str` and `&[u8]` borrow directly from the RPC buffer. Other references borrow from a
+/// decoded value owned by the exported function.
+fn extract_name_and_decoding(
+    reader_ident: Ident,
+    token: &FnArg,
+) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let pat = typed_argument(token)?;
+    let name = argument_name(pat)?;
+    let variable_identifier = format_ident!("tmp_{}", name);
+    let owned_identifier = format_ident!("owned_{}", name);
+
+    let decoding = match pat.ty.as_ref() {
+        Type::Reference(reference) if reference.mutability.is_some() => {
+            return Err(syn::Error::new_spanned(
+                reference,
+                "RPC arguments cannot be mutable references",
+            ))
         }
+        Type::Reference(reference) => match reference.elem.as_ref() {
+            Type::Path(path) if path.path.is_ident("str") => quote! {
+                let #variable_identifier =
+                    ::pbc_contract::serialization::read_str(&mut #reader_ident);
+            },
+            Type::Slice(slice) if is_ident(&slice.elem, "u8") => quote! {
+                let #variable_identifier =
+                    ::pbc_contract::serialization::read_bytes(&mut #reader_ident);
+            },
+            Type::Slice(slice) => {
+                let elem = &slice.elem;
+                quote! {
+                    let #owned_identifier = <Vec<#elem> as ::pbc_contract::serialization::ReadWrite>::read_from(&mut #reader_ident);
+                    let #variable_identifier = #owned_identifier.as_slice();
+                }
+            }
+            elem => {
+                let expr = instantiating_expression(reader_ident, elem)?;
+                quote! {
+                    let #owned_identifier = #expr;
+                    let #variable_identifier = &#owned_identifier;
+                }
+            }
+        },
+        ty => {
+            let expr = instantiating_expression(reader_ident, ty)?;
+            quote! { let #variable_identifier = #expr; }
+        }
+    };
+    Ok((quote! {#variable_identifier}, decoding))
+}
+
+fn typed_argument(token: &FnArg) -> syn::Result<&syn::PatType> {
+    match token {
+        FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+            receiver,
+            "Contract functions cannot take `self`",
+        )),
+        FnArg::Typed(pat) => Ok(pat),
     }
 }
+
+fn argument_name(pat: &syn::PatType) -> syn::Result<&Ident> {
+    match pat.pat.as_ref() {
+        syn::Pat::Ident(pat_ident) => Ok(&pat_ident.ident),
+        other => Err(syn::Error::new_spanned(
+            other,
+            "Arguments of contract functions must be plain identifiers",
+        )),
+    }
+}
+
+fn is_ident(ty: &Type, ident: &str) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident(ident))
+}
+
+/// Expression reading an owned value of type `ty` from `reader_ident`.
+fn instantiating_expression(reader_ident: Ident, ty: &Type) -> syn::Result<TokenStream2> {
+    match ty {
+        Type::Path(path) => Ok(generate_instantiating_expression(
+            reader_ident,
+            path.clone(),
+        )),
+        Type::Tuple(_) | Type::Array(_) => Ok(quote! {
+            <#ty as ::pbc_contract::serialization::ReadWrite>::read_from(&mut #reader_ident)
+        }),
+        Type::Paren(paren) => instantiating_expression(reader_ident, &paren.elem),
+        other => Err(syn::Error::new_spanned(
+            other,
+            "Unsupported argument type, expected a type implementing `ReadWrite` or a reference to one",
+        )),
+    }
+}
+
+fn generate_instantiating_expression(reader_ident: Ident, path: TypePath) -> TokenStream2 {
+    quote! {<#path as ::pbc_contract::serialization::ReadWrite>::read_from(&mut #reader_ident)}
+}
@@ -0,0 +1,88 @@
+//! The items of a contract module, collected by `#[contract]` while it expands the module.
+//!
+//! The `execute` dispatcher and the ABI export are generated from the registry once every item
+//! of the module has been expanded.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+
+pub struct Registry {
+    /// The name of the `#[state]` struct, which is known before any other item is expanded.
+    pub state: String,
+    pub events: Vec<String>,
+    pub functions: Vec<FunctionEntry>,
+    pub upgrade: Option<UpgradeEntry>,
+}
+
+/// A contract function, with its argument and return types kept as source text.
+pub struct FunctionEntry {
+    pub kind: FunctionKind,
+    pub name: String,
+    pub shortname: Option<u32>,
+    pub arguments: Vec<(String, String)>,
+    pub returns: Option<String>,
+    pub error: Option<String>,
+}
+
+/// The `#[upgrade]` function, with the previous state and error types kept as source text.
+pub struct UpgradeEntry {
+    pub name: String,
+    pub old_state: String,
+    pub error: Option<String>,
+}
+
+/// Mirrors `pbc_contract_common::abi::FunctionKind`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FunctionKind {
+    Init,
+    Action,
+    View,
+}
+
+impl ToTokens for FunctionKind {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let variant = match self {
+            FunctionKind::Init => format_ident!("Init"),
+            FunctionKind::Action => format_ident!("Action"),
+            FunctionKind::View => format_ident!("View"),
+        };
+        tokens.extend(quote! { ::pbc_contract::abi::FunctionKind::#variant });
+    }
+}
+
+impl Registry {
+    pub fn new(state: String) -> Self {
+        Registry {
+            state,
+            events: Vec::new(),
+            functions: Vec::new(),
+            upgrade: None,
+        }
+    }
+
+    pub fn register_event(&mut self, name: String) {
+        if !self.events.contains(&name) {
+            self.events.push(name);
+        }
+    }
+
+    /// Register a contract function.
+    ///
+    /// Shortnames are checked for collisions by the `const` each function emits, see
+    /// `shortname_marker`.
+    pub fn register_function(&mut self, entry: FunctionEntry) {
+        self.functions.push(entry);
+    }
+
+    /// Register the `#[upgrade]` function, failing if the contract already has another one.
+    pub fn register_upgrade(&mut self, entry: UpgradeEntry) -> Result<(), String> {
+        if let Some(existing) = &self.upgrade {
+            return Err(format!(
+                "The contract already has the upgrade function `{}`",
+                existing.name
+            ));
+        }
+        self.upgrade = Some(entry);
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::abi::{ContractAbi, FunctionKind, SchemaAbi, ABI_VERSION};
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod vault {
+    use super::*;
+
+    #[state]
+    pub struct VaultState {
+        pub limit: u64,
+        pub stored: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext, limit: u64) -> VaultState {
+        VaultState { limit, stored: 0 }
+    }
+
+    #[action]
+    pub fn deposit(
+        _ctx: ContractContext,
+        state: VaultState,
+        amount: u64,
+    ) -> Result<VaultState, String> {
+        let stored = state.stored + amount;
+        if stored > state.limit {
+            return Err(String::from("Vault is full"));
+        }
+        Ok(VaultState { stored, ..state })
+    }
+}
+
+use vault::*;
+
+fn exported_abi() -> ContractAbi {
+    let len = export_contract_schema_json(std::ptr::null_mut(), 0);
+    let mut buffer = vec![0u8; len];
+    assert_eq!(
+        export_contract_schema_json(buffer.as_mut_ptr(), buffer.len()),
+        len
+    );
+    serde_json::from_slice(&buffer).unwrap()
+}
+
+#[test]
+fn abi_reports_its_length_without_writing_to_a_small_buffer() {
+    let mut buffer = vec![0u8; 8];
+    let len = export_contract_schema_json(buffer.as_mut_ptr(), buffer.len());
+
+    assert!(len > buffer.len());
+    assert_eq!(buffer, vec![0u8; 8]);
+}
+
+#[test]
+fn abi_has_version_and_state() {
+    let abi = exported_abi();
+
+    assert_eq!(abi.version, ABI_VERSION);
+    assert_eq!(abi.state, SchemaAbi::of::<VaultState>());
+}
+
+#[test]
+fn abi_lists_init_without_shortname() {
+    let abi = exported_abi();
+    let init = abi
+        .functions
+        .iter()
+        .find(|f| f.name == "initialize")
+        .unwrap();
+
+    assert_eq!(init.kind, FunctionKind::Init);
+    assert_eq!(init.shortname, None);
+    assert_eq!(init.arguments.len(), 1);
+    assert_eq!(init.arguments[0].name, "limit");
+    assert_eq!(init.error, None);
+}
+
+#[test]
+fn abi_lists_actions_with_argument_names_and_error() {
+    let abi = exported_abi();
+    let deposit = abi.functions.iter().find(|f| f.name == "deposit").unwrap();
+
+    assert_eq!(deposit.kind, FunctionKind::Action);
+    assert!(deposit.shortname.is_some());
+    assert_eq!(deposit.arguments[0].name, "amount");
+    assert_eq!(deposit.error, Some(SchemaAbi::of::<String>()));
+}
+
+#[test]
+fn binary_abi_converts_to_the_json_abi() {
+    let len = export_contract_schema_binary(std::ptr::null_mut(), 0);
+    let mut buffer = vec![0u8; len];
+    export_contract_schema_binary(buffer.as_mut_ptr(), buffer.len());
+
+    assert_eq!(ContractAbi::from_binary(&buffer), exported_abi());
+}
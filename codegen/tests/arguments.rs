@@ -0,0 +1,134 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use pbc_contract_common::abi::SchemaAbi;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::serialization::ReadWrite;
+
+use common::{context, execute_rpc, exported_abi, state_of};
+
+#[contract]
+mod registry {
+    use super::*;
+
+    #[state]
+    pub struct RegistryState {
+        pub symbol: [u8; 4],
+        pub name: String,
+        pub balances: BTreeMap<Address, u64>,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> RegistryState {
+        RegistryState {
+            symbol: [0; 4],
+            name: String::new(),
+            balances: BTreeMap::new(),
+        }
+    }
+
+    #[action]
+    pub fn set_symbol(
+        _ctx: ContractContext,
+        state: RegistryState,
+        symbol: [u8; 4],
+    ) -> RegistryState {
+        RegistryState { symbol, ..state }
+    }
+
+    #[action]
+    pub fn rename(_ctx: ContractContext, state: RegistryState, name: &str) -> RegistryState {
+        RegistryState {
+            name: name.to_string(),
+            ..state
+        }
+    }
+
+    #[action]
+    pub fn credit(
+        _ctx: ContractContext,
+        state: RegistryState,
+        entry: (Address, u64),
+    ) -> RegistryState {
+        batch(_ctx, state, &[entry])
+    }
+
+    #[action]
+    pub fn batch(
+        _ctx: ContractContext,
+        mut state: RegistryState,
+        recipients: &[(Address, u64)],
+    ) -> RegistryState {
+        for (recipient, amount) in recipients {
+            *state.balances.entry(*recipient).or_insert(0) += amount;
+        }
+        state
+    }
+}
+
+use registry::*;
+
+fn invoke(action: &str, arguments: &[u8]) -> RegistryState {
+    let abi = exported_abi(export_contract_schema_json);
+    let function = abi.functions.iter().find(|f| f.name == action).unwrap();
+
+    let mut rpc = function.shortname.unwrap().to_be_bytes().to_vec();
+    rpc.extend_from_slice(arguments);
+    state_of(&execute_rpc(execute, &initialize(context()), &rpc))
+}
+
+#[test]
+fn array_arguments_are_read_without_length() {
+    let state = invoke("set_symbol", b"MPC!");
+
+    assert_eq!(&state.symbol, b"MPC!");
+}
+
+#[test]
+fn str_arguments_are_read_as_strings() {
+    let mut arguments = Vec::new();
+    String::from("Registry").write_to(&mut arguments).unwrap();
+
+    let state = invoke("rename", &arguments);
+
+    assert_eq!(state.name, "Registry");
+}
+
+#[test]
+fn tuple_and_slice_arguments_are_read_element_by_element() {
+    let alice = Address::Account([5u8; 20]);
+    let bob = Address::Account([6u8; 20]);
+    let mut arguments = Vec::new();
+    vec![(alice, 10u64), (bob, 20u64), (alice, 1u64)]
+        .write_to(&mut arguments)
+        .unwrap();
+
+    let state = invoke("batch", &arguments);
+
+    assert_eq!(state.balances.get(&alice), Some(&11));
+    assert_eq!(state.balances.get(&bob), Some(&20));
+
+    let mut arguments = Vec::new();
+    (bob, 7u64).write_to(&mut arguments).unwrap();
+    let state = invoke("credit", &arguments);
+    assert_eq!(state.balances.get(&bob), Some(&7));
+}
+
+#[test]
+fn abi_lists_references_as_their_encoded_types() {
+    let abi = exported_abi(export_contract_schema_json);
+
+    let rename = abi.functions.iter().find(|f| f.name == "rename").unwrap();
+    assert_eq!(rename.arguments[0].schema, SchemaAbi::of::<String>());
+    let batch = abi.functions.iter().find(|f| f.name == "batch").unwrap();
+    assert_eq!(
+        batch.arguments[0].schema,
+        SchemaAbi::of::<Vec<(Address, u64)>>()
+    );
+}
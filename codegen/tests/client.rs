@@ -0,0 +1,84 @@
+#![cfg(feature = "client")]
+
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::result::STATUS_OK;
+use pbc_contract_common::serialization::ReadWrite;
+
+use common::execute_rpc;
+
+#[contract]
+mod ledger {
+    use super::*;
+
+    #[state]
+    pub struct LedgerState {
+        pub entries: Vec<(Address, u64)>,
+        pub memo: String,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> LedgerState {
+        LedgerState {
+            entries: vec![],
+            memo: String::new(),
+        }
+    }
+
+    #[action(shortname = 0x01)]
+    pub fn record(_ctx: ContractContext, state: &mut LedgerState, to: Address, amount: u64) {
+        state.entries.push((to, amount));
+    }
+
+    #[action]
+    pub fn annotate(_ctx: ContractContext, state: &mut LedgerState, memo: &str) {
+        state.memo = memo.to_string();
+    }
+}
+
+use ledger::*;
+
+/// Execute `rpc`, decoding the new state with the client.
+fn apply(state: &LedgerState, rpc: &[u8]) -> LedgerState {
+    let result = execute_rpc(execute, state, rpc);
+    assert_eq!(result[0], STATUS_OK);
+    let state_bytes = Vec::<u8>::read_from(&mut &result[1..]);
+    client::decode_state(&state_bytes)
+}
+
+#[test]
+fn client_encodes_shortname_and_arguments() {
+    let to = Address::Account([7u8; 20]);
+    let rpc = client::record(to, 5);
+
+    let mut expected = vec![0, 0, 0, 1];
+    to.write_to(&mut expected).unwrap();
+    5u64.write_to(&mut expected).unwrap();
+    assert_eq!(rpc, expected);
+}
+
+#[test]
+fn client_rpc_is_accepted_by_the_contract() {
+    let state = LedgerState {
+        entries: vec![],
+        memo: String::new(),
+    };
+    let to = Address::Account([7u8; 20]);
+
+    let state = apply(&state, &client::record(to, 5));
+    let state = apply(&state, &client::annotate(String::from("first")));
+
+    assert_eq!(
+        state,
+        LedgerState {
+            entries: vec![(to, 5)],
+            memo: String::from("first"),
+        }
+    );
+}
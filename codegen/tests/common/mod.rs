@@ -0,0 +1,103 @@
+//! Fixtures shared by the tests invoking the exports generated by `#[contract]`.
+
+#![allow(dead_code)]
+
+use pbc_contract_common::abi::ContractAbi;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::memory::take_return_descriptor;
+use pbc_contract_common::result::{STATUS_ERROR, STATUS_OK};
+use pbc_contract_common::serialization::ReadWrite;
+
+/// The signature of `execute`, of the exported actions and views and of `upgrade`.
+pub type Export = extern "C" fn(*const u8, usize, *const u8, usize, *const u8, usize) -> *mut u8;
+
+/// The signature of `init`.
+pub type InitExport = extern "C" fn(*const u8, usize, *const u8, usize) -> *mut u8;
+
+/// The signature of the ABI exports.
+pub type AbiExport = extern "C" fn(*mut u8, usize) -> usize;
+
+pub const OWNER: Address = Address::Account([1u8; 20]);
+
+/// The context of an invocation by the owner at block time 1.
+pub fn context() -> ContractContext {
+    ContractContext {
+        owner: OWNER,
+        contract_address: Address::PublicContract([2u8; 20]),
+        sender: OWNER,
+        block_time: 1,
+        block_production_time: 2,
+        current_transaction: [3u8; 32],
+        original_transaction: [4u8; 32],
+    }
+}
+
+pub fn bytes_of<T: ReadWrite>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+/// Call `export` the way the host does, returning the result buffer.
+pub fn invoke<S: ReadWrite>(
+    export: Export,
+    context: &ContractContext,
+    state: &S,
+    rpc: &[u8],
+) -> Vec<u8> {
+    let ctx_bytes = bytes_of(context);
+    let state_bytes = bytes_of(state);
+
+    let descriptor = export(
+        ctx_bytes.as_ptr(),
+        ctx_bytes.len(),
+        state_bytes.as_ptr(),
+        state_bytes.len(),
+        rpc.as_ptr(),
+        rpc.len(),
+    );
+    unsafe { take_return_descriptor(descriptor) }
+}
+
+/// Call `execute` with the default context.
+pub fn execute_rpc<S: ReadWrite>(execute: Export, state: &S, rpc: &[u8]) -> Vec<u8> {
+    invoke(execute, &context(), state, rpc)
+}
+
+/// Call `init` with the default context.
+pub fn init_rpc(init: InitExport, rpc: &[u8]) -> Vec<u8> {
+    let ctx_bytes = bytes_of(&context());
+
+    let descriptor = init(ctx_bytes.as_ptr(), ctx_bytes.len(), rpc.as_ptr(), rpc.len());
+    unsafe { take_return_descriptor(descriptor) }
+}
+
+/// The new state of a successful invocation.
+pub fn state_of<S: ReadWrite>(result: &[u8]) -> S {
+    assert_eq!(result[0], STATUS_OK);
+    let state_bytes = Vec::<u8>::read_from(&mut &result[1..]);
+    S::read_from(&mut state_bytes.as_slice())
+}
+
+/// The value returned by a view.
+pub fn value_of<V: ReadWrite>(result: &[u8]) -> V {
+    assert_eq!(result[0], STATUS_OK);
+    let value_bytes = Vec::<u8>::read_from(&mut &result[1..]);
+    V::read_from(&mut value_bytes.as_slice())
+}
+
+/// The error of a failed invocation.
+pub fn error_of<E: ReadWrite>(result: &[u8]) -> E {
+    assert_eq!(result[0], STATUS_ERROR);
+    let error_bytes = Vec::<u8>::read_from(&mut &result[1..]);
+    E::read_from(&mut error_bytes.as_slice())
+}
+
+/// The json ABI written by `export`.
+pub fn exported_abi(export: AbiExport) -> ContractAbi {
+    let len = export(std::ptr::null_mut(), 0);
+    let mut buffer = vec![0u8; len];
+    assert_eq!(export(buffer.as_mut_ptr(), buffer.len()), len);
+    serde_json::from_slice(&buffer).unwrap()
+}
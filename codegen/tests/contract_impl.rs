@@ -0,0 +1,114 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::abi::FunctionKind;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::result::STATUS_ERROR;
+
+use common::{bytes_of, execute_rpc, exported_abi, init_rpc, state_of};
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        pub count: u64,
+    }
+
+    #[contract]
+    impl CounterState {
+        #[init]
+        pub fn initialize(_ctx: ContractContext, start: u64) -> Self {
+            CounterState { count: start }
+        }
+
+        #[action(shortname = 0x01)]
+        pub fn increment(&mut self, _ctx: ContractContext, by: u64) {
+            self.count = self.add(by);
+        }
+
+        #[action(shortname = 0x02)]
+        pub fn decrement(&mut self, _ctx: ContractContext, by: u64) -> Result<(), String> {
+            if by > self.count {
+                return Err(String::from("Counter cannot go below zero"));
+            }
+            self.count -= by;
+            Ok(())
+        }
+
+        #[action(shortname = 0x03)]
+        pub fn reset(self, _ctx: ContractContext) -> Self {
+            CounterState { count: 0 }
+        }
+
+        #[view]
+        pub fn count(&self, _ctx: ContractContext) -> u64 {
+            self.count
+        }
+
+        fn add(&self, by: u64) -> u64 {
+            self.count + by
+        }
+    }
+}
+
+use counter::*;
+
+fn execute_count(count: u64, rpc: &[u8]) -> Vec<u8> {
+    execute_rpc(execute, &CounterState { count }, rpc)
+}
+
+#[test]
+fn init_method_creates_the_state() {
+    let result = init_rpc(init, &bytes_of(&5u64));
+
+    assert_eq!(state_of::<CounterState>(&result), CounterState { count: 5 });
+}
+
+#[test]
+fn mut_self_actions_mutate_the_state() {
+    let result = execute_count(1, &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+
+    assert_eq!(state_of::<CounterState>(&result), CounterState { count: 3 });
+}
+
+#[test]
+fn mut_self_actions_may_fail() {
+    let result = execute_count(1, &[0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2]);
+
+    assert_eq!(result[0], STATUS_ERROR);
+}
+
+#[test]
+fn self_actions_return_the_new_state() {
+    let result = execute_count(7, &[0, 0, 0, 3]);
+
+    assert_eq!(state_of::<CounterState>(&result), CounterState { count: 0 });
+}
+
+#[test]
+fn abi_lists_annotated_methods_only() {
+    let abi = exported_abi(export_contract_schema_json);
+
+    let functions: Vec<(FunctionKind, &str)> = abi
+        .functions
+        .iter()
+        .map(|f| (f.kind, f.name.as_str()))
+        .collect();
+    assert_eq!(
+        functions,
+        vec![
+            (FunctionKind::Init, "initialize"),
+            (FunctionKind::Action, "increment"),
+            (FunctionKind::Action, "decrement"),
+            (FunctionKind::Action, "reset"),
+            (FunctionKind::View, "count"),
+        ]
+    );
+    let initialize = &abi.functions[0];
+    assert_eq!(initialize.arguments[0].name, "start");
+}
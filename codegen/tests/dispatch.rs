@@ -0,0 +1,105 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::result::STATUS_OK;
+use pbc_contract_common::serialization::{ReadInt, ReadWrite};
+
+use common::{error_of, execute_rpc, exported_abi, state_of};
+
+#[contract]
+mod switch {
+    use super::*;
+
+    #[state(manual_serialization)]
+    pub struct SwitchState {
+        pub on: u64,
+    }
+
+    impl ReadWrite for SwitchState {
+        fn read_from<T: std::io::Read>(reader: &mut T) -> Self {
+            SwitchState {
+                on: u64::read_from(reader),
+            }
+        }
+
+        fn write_to<T: std::io::Write>(&self, writer: &mut T) -> std::io::Result<()> {
+            self.on.write_to(writer)
+        }
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> SwitchState {
+        SwitchState { on: 0 }
+    }
+
+    #[action(shortname = 0x01)]
+    pub fn turn_on(_ctx: ContractContext, _state: SwitchState) -> SwitchState {
+        SwitchState { on: 1 }
+    }
+
+    #[action]
+    pub fn set(_ctx: ContractContext, _state: SwitchState, on: u64) -> SwitchState {
+        SwitchState { on }
+    }
+}
+
+use switch::*;
+
+fn shortname_of(name: &str) -> u32 {
+    let abi = exported_abi(export_contract_schema_json);
+    let function = abi.functions.iter().find(|f| f.name == name).unwrap();
+    function.shortname.unwrap()
+}
+
+fn execute_switch(rpc: &[u8]) -> Vec<u8> {
+    execute_rpc(execute, &SwitchState { on: 0 }, rpc)
+}
+
+#[test]
+fn execute_routes_on_shortname() {
+    let mut rpc = shortname_of("set").to_be_bytes().to_vec();
+    7u64.write_to(&mut rpc).unwrap();
+
+    let result = execute_switch(&rpc);
+
+    assert_eq!(result[0], STATUS_OK);
+    let mut reader = &result[1..];
+    let state_bytes = Vec::<u8>::read_from(&mut reader);
+    assert_eq!(
+        SwitchState::read_from(&mut state_bytes.as_slice()),
+        SwitchState { on: 7 }
+    );
+    assert_eq!(reader.read_u32_be(), 0);
+}
+
+#[test]
+fn execute_routes_on_pinned_shortname() {
+    assert_eq!(shortname_of("turn_on"), 0x01);
+
+    let result = execute_switch(&[0, 0, 0, 1]);
+
+    assert_eq!(state_of::<SwitchState>(&result), SwitchState { on: 1 });
+}
+
+#[test]
+fn execute_rejects_unknown_shortname() {
+    let unknown = 0xffff_ffffu32;
+    assert_ne!(shortname_of("set"), unknown);
+    let result = execute_switch(&unknown.to_be_bytes());
+
+    assert_eq!(
+        error_of::<String>(&result),
+        format!("Unknown action shortname {:#010x}", unknown)
+    );
+}
+
+#[test]
+fn execute_rejects_rpc_without_shortname() {
+    let result = execute_switch(&[1, 2]);
+
+    assert_eq!(error_of::<String>(&result), "Missing action shortname");
+}
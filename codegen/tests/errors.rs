@@ -0,0 +1,144 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::abi::SchemaAbi;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::result::STATUS_ERROR;
+use pbc_contract_common::serialization::ReadWrite;
+
+use common::{bytes_of, error_of, execute_rpc, exported_abi, init_rpc, state_of};
+
+#[contract]
+mod escrow {
+    use super::*;
+
+    #[state]
+    pub struct EscrowState {
+        pub deposited: u64,
+        pub limit: u64,
+    }
+
+    #[derive(Debug, PartialEq, Eq, pbc_contract::Reflection)]
+    #[reflection(crate = "::pbc_contract::reflection")]
+    pub struct LimitExceeded {
+        pub limit: u64,
+        pub requested: u64,
+    }
+
+    impl ReadWrite for LimitExceeded {
+        fn read_from<T: std::io::Read>(reader: &mut T) -> Self {
+            LimitExceeded {
+                limit: u64::read_from(reader),
+                requested: u64::read_from(reader),
+            }
+        }
+
+        fn write_to<T: std::io::Write>(&self, writer: &mut T) -> std::io::Result<()> {
+            self.limit.write_to(writer)?;
+            self.requested.write_to(writer)
+        }
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext, limit: u64) -> Result<EscrowState, String> {
+        if limit == 0 {
+            return Err(String::from("The limit must be positive"));
+        }
+        Ok(EscrowState {
+            deposited: 0,
+            limit,
+        })
+    }
+
+    #[action(shortname = 0x01)]
+    pub fn deposit(
+        _ctx: ContractContext,
+        state: EscrowState,
+        amount: u64,
+    ) -> Result<EscrowState, LimitExceeded> {
+        let deposited = state.deposited + amount;
+        if deposited > state.limit {
+            return Err(LimitExceeded {
+                limit: state.limit,
+                requested: deposited,
+            });
+        }
+        Ok(EscrowState { deposited, ..state })
+    }
+}
+
+use escrow::*;
+
+fn deposit_rpc(amount: u64) -> Vec<u8> {
+    let mut rpc = vec![0, 0, 0, 1];
+    amount.write_to(&mut rpc).unwrap();
+    rpc
+}
+
+#[test]
+fn init_returning_ok_creates_the_state() {
+    let result = init_rpc(init, &bytes_of(&10u64));
+
+    assert_eq!(
+        state_of::<EscrowState>(&result),
+        EscrowState {
+            deposited: 0,
+            limit: 10,
+        }
+    );
+}
+
+#[test]
+fn init_returning_err_fails_with_the_error() {
+    let result = init_rpc(init, &bytes_of(&0u64));
+
+    assert_eq!(error_of::<String>(&result), "The limit must be positive");
+}
+
+#[test]
+fn custom_errors_follow_the_error_status() {
+    let state = EscrowState {
+        deposited: 8,
+        limit: 10,
+    };
+
+    let result = execute_rpc(execute, &state, &deposit_rpc(5));
+
+    let error = LimitExceeded {
+        limit: 10,
+        requested: 13,
+    };
+    let mut expected = vec![STATUS_ERROR];
+    bytes_of(&error).write_to(&mut expected).unwrap();
+    assert_eq!(result, expected);
+    assert_eq!(error_of::<LimitExceeded>(&result), error);
+}
+
+#[test]
+fn actions_returning_ok_with_a_custom_error_type_update_the_state() {
+    let state = EscrowState {
+        deposited: 8,
+        limit: 10,
+    };
+
+    let result = execute_rpc(execute, &state, &deposit_rpc(2));
+
+    assert_eq!(state_of::<EscrowState>(&result).deposited, 10);
+}
+
+#[test]
+fn abi_lists_the_error_types() {
+    let abi = exported_abi(export_contract_schema_json);
+
+    let initialize = abi
+        .functions
+        .iter()
+        .find(|f| f.name == "initialize")
+        .unwrap();
+    assert_eq!(initialize.error, Some(SchemaAbi::of::<String>()));
+    let deposit = abi.functions.iter().find(|f| f.name == "deposit").unwrap();
+    assert_eq!(deposit.error, Some(SchemaAbi::of::<LimitExceeded>()));
+}
@@ -0,0 +1,110 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::{emit, take_pending_events, Event};
+use pbc_contract_common::result::STATUS_OK;
+use pbc_contract_common::serialization::{ReadInt, ReadWrite};
+
+use common::{bytes_of, execute_rpc, exported_abi, init_rpc};
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        pub count: u64,
+    }
+
+    #[event]
+    pub struct Incremented {
+        pub by: u64,
+        pub total: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> CounterState {
+        CounterState { count: 0 }
+    }
+
+    #[action]
+    pub fn increment(_ctx: ContractContext, state: CounterState, by: u64) -> CounterState {
+        let total = state.count + by;
+        emit(&Incremented { by, total });
+        CounterState { count: total }
+    }
+}
+
+use counter::*;
+
+#[test]
+fn emitted_events_are_encoded_with_their_id() {
+    let event = Incremented { by: 2, total: 5 };
+    emit(&event);
+
+    let events = take_pending_events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].0, Incremented::event_id());
+    assert_eq!(Incremented::read_from(&mut events[0].1.as_slice()), event);
+    assert!(take_pending_events().is_empty());
+}
+
+#[test]
+fn abi_lists_events() {
+    let mut buffer = vec![0u8; 4096];
+    let len = export_contract_schema_json(buffer.as_mut_ptr(), buffer.len());
+    let abi: serde_json::Value = serde_json::from_slice(&buffer[..len]).unwrap();
+
+    assert_eq!(abi["state"]["type_name"], "CounterState");
+    assert_eq!(abi["events"][0]["name"], "Incremented");
+    assert_eq!(abi["events"][0]["event_id"], Incremented::event_id());
+    assert_eq!(abi["events"][0]["schema"]["members"][1]["name"], "total");
+}
+
+#[test]
+fn init_hands_result_to_host_as_return_descriptor() {
+    let result = init_rpc(init, &[]);
+
+    let mut reader = &result[1..];
+    assert_eq!(result[0], STATUS_OK);
+    let state_bytes = Vec::<u8>::read_from(&mut reader);
+    assert_eq!(
+        CounterState::read_from(&mut state_bytes.as_slice()),
+        CounterState { count: 0 }
+    );
+    assert_eq!(reader.read_u32_be(), 0);
+}
+
+#[test]
+fn action_events_follow_the_new_state_in_the_result() {
+    let shortname = exported_abi(export_contract_schema_json)
+        .functions
+        .iter()
+        .find(|function| function.name == "increment")
+        .and_then(|function| function.shortname)
+        .unwrap();
+    let mut rpc = shortname.to_be_bytes().to_vec();
+    rpc.extend(bytes_of(&2u64));
+
+    let result = execute_rpc(execute, &CounterState { count: 3 }, &rpc);
+
+    let mut reader = &result[1..];
+    assert_eq!(result[0], STATUS_OK);
+    let state_bytes = Vec::<u8>::read_from(&mut reader);
+    assert_eq!(
+        CounterState::read_from(&mut state_bytes.as_slice()),
+        CounterState { count: 5 }
+    );
+    assert_eq!(reader.read_u32_be(), 1);
+    assert_eq!(reader.read_u32_be(), Incremented::event_id());
+    let payload = Vec::<u8>::read_from(&mut reader);
+    assert_eq!(
+        Incremented::read_from(&mut payload.as_slice()),
+        Incremented { by: 2, total: 5 }
+    );
+    assert!(reader.is_empty());
+}
@@ -0,0 +1,172 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::abi::SchemaAbi;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::serialization::ReadWrite;
+
+use common::{context, error_of, exported_abi, invoke, state_of, OWNER};
+
+const OTHER: Address = Address::Account([5u8; 20]);
+
+#[contract]
+mod vote {
+    use super::*;
+
+    #[state]
+    pub struct VoteState {
+        pub votes: u64,
+        pub closed_at: Option<i64>,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> VoteState {
+        VoteState {
+            votes: 0,
+            closed_at: None,
+        }
+    }
+
+    mod rules {
+        use pbc_contract_common::context::ContractContext;
+
+        pub fn before_deadline(context: &ContractContext) -> Result<(), String> {
+            if context.block_time > 100 {
+                return Err(String::from("Voting has ended"));
+            }
+            Ok(())
+        }
+    }
+
+    #[action(shortname = 0x01, guard = rules::before_deadline)]
+    pub fn vote(_ctx: ContractContext, state: &mut VoteState) {
+        state.votes += 1;
+    }
+
+    #[action(shortname = 0x02, only_owner)]
+    pub fn close(ctx: ContractContext, state: &mut VoteState) {
+        state.closed_at = Some(ctx.block_time);
+    }
+
+    #[action(shortname = 0x03, only_owner, guard = rules::before_deadline)]
+    pub fn reset(_ctx: ContractContext, state: VoteState) -> Result<VoteState, String> {
+        Ok(VoteState { votes: 0, ..state })
+    }
+
+    #[derive(Debug, PartialEq, Eq, pbc_contract::Reflection)]
+    #[reflection(crate = "::pbc_contract::reflection")]
+    pub struct VoteError {
+        pub code: u32,
+        pub message: String,
+    }
+
+    impl From<String> for VoteError {
+        fn from(message: String) -> Self {
+            VoteError { code: 0, message }
+        }
+    }
+
+    impl ReadWrite for VoteError {
+        fn read_from<T: std::io::Read>(reader: &mut T) -> Self {
+            VoteError {
+                code: u32::read_from(reader),
+                message: String::read_from(reader),
+            }
+        }
+
+        fn write_to<T: std::io::Write>(&self, writer: &mut T) -> std::io::Result<()> {
+            self.code.write_to(writer)?;
+            self.message.write_to(writer)
+        }
+    }
+
+    #[action(shortname = 0x04, only_owner)]
+    pub fn reopen(_ctx: ContractContext, state: &mut VoteState) -> Result<(), VoteError> {
+        if state.closed_at.is_none() {
+            return Err(VoteError {
+                code: 1,
+                message: String::from("Voting is not closed"),
+            });
+        }
+        state.closed_at = None;
+        Ok(())
+    }
+}
+
+use vote::*;
+
+fn execute_as(sender: Address, block_time: i64, shortname: u32) -> Vec<u8> {
+    let context = ContractContext {
+        sender,
+        block_time,
+        ..context()
+    };
+    let state = VoteState {
+        votes: 3,
+        closed_at: None,
+    };
+    invoke(execute, &context, &state, &shortname.to_be_bytes())
+}
+
+#[test]
+fn only_owner_rejects_other_senders() {
+    assert_eq!(
+        error_of::<String>(&execute_as(OTHER, 1, 0x02)),
+        "Only the owner of the contract can invoke this action"
+    );
+    assert_eq!(
+        state_of::<VoteState>(&execute_as(OWNER, 1, 0x02)).closed_at,
+        Some(1)
+    );
+}
+
+#[test]
+fn custom_guard_checks_the_context() {
+    assert_eq!(
+        state_of::<VoteState>(&execute_as(OTHER, 100, 0x01)).votes,
+        4
+    );
+    assert_eq!(
+        error_of::<String>(&execute_as(OTHER, 101, 0x01)),
+        "Voting has ended"
+    );
+}
+
+#[test]
+fn guards_run_in_declaration_order() {
+    assert_eq!(
+        error_of::<String>(&execute_as(OTHER, 101, 0x03)),
+        "Only the owner of the contract can invoke this action"
+    );
+    assert_eq!(
+        error_of::<String>(&execute_as(OWNER, 101, 0x03)),
+        "Voting has ended"
+    );
+    assert_eq!(state_of::<VoteState>(&execute_as(OWNER, 1, 0x03)).votes, 0);
+}
+
+#[test]
+fn guard_errors_are_converted_into_the_error_of_the_action() {
+    assert_eq!(
+        error_of::<VoteError>(&execute_as(OTHER, 1, 0x04)),
+        VoteError {
+            code: 0,
+            message: String::from("Only the owner of the contract can invoke this action"),
+        }
+    );
+    assert_eq!(error_of::<VoteError>(&execute_as(OWNER, 1, 0x04)).code, 1);
+}
+
+#[test]
+fn abi_lists_guard_errors_as_strings() {
+    let abi = exported_abi(export_contract_schema_json);
+
+    let close = abi.functions.iter().find(|f| f.name == "close").unwrap();
+    assert_eq!(close.error, Some(SchemaAbi::of::<String>()));
+    let reopen = abi.functions.iter().find(|f| f.name == "reopen").unwrap();
+    assert_eq!(reopen.error, Some(SchemaAbi::of::<VoteError>()));
+}
@@ -0,0 +1,77 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::serialization::ReadWrite;
+
+use common::{error_of, execute_rpc, state_of};
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        pub count: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> CounterState {
+        CounterState { count: 0 }
+    }
+
+    #[action(shortname = 0x01)]
+    pub fn increment(_ctx: ContractContext, state: &mut CounterState, by: u64) {
+        state.count += by;
+    }
+
+    #[action(shortname = 0x02)]
+    pub fn decrement(
+        _ctx: ContractContext,
+        state: &mut CounterState,
+        by: u64,
+    ) -> Result<(), String> {
+        if by > state.count {
+            return Err(String::from("Counter cannot go below zero"));
+        }
+        state.count -= by;
+        Ok(())
+    }
+
+    #[view]
+    pub fn count(_ctx: ContractContext, state: &CounterState) -> u64 {
+        state.count
+    }
+}
+
+use counter::*;
+
+fn execute_count(count: u64, shortname: u32, by: u64) -> Vec<u8> {
+    let mut rpc = shortname.to_be_bytes().to_vec();
+    by.write_to(&mut rpc).unwrap();
+    execute_rpc(execute, &CounterState { count }, &rpc)
+}
+
+#[test]
+fn mutated_state_becomes_the_new_state() {
+    let result = execute_count(1, 0x01, 2);
+
+    assert_eq!(state_of::<CounterState>(&result), CounterState { count: 3 });
+}
+
+#[test]
+fn mutated_state_is_kept_on_ok() {
+    let result = execute_count(5, 0x02, 2);
+
+    assert_eq!(state_of::<CounterState>(&result), CounterState { count: 3 });
+}
+
+#[test]
+fn mutated_state_is_discarded_on_err() {
+    let result = execute_count(1, 0x02, 2);
+
+    assert_eq!(error_of::<String>(&result), "Counter cannot go below zero");
+}
@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+#[contract]
+mod counter {
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[action]
+    pub struct Increment;
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[state]
+pub struct CounterState {
+    count: u64,
+}
+
+#[action]
+pub fn increment(_ctx: ContractContext, state: CounterState) -> CounterState {
+    CounterState {
+        count: state.count + 1,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[action(name = "inc")]
+    pub fn increment(_ctx: ContractContext, state: CounterState) -> CounterState {
+        CounterState {
+            count: state.count + 1,
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[action]
+    pub fn add(_ctx: ContractContext, state: CounterState, amount: impl Into<u64>) -> CounterState {
+        CounterState {
+            count: state.count + amount.into(),
+        }
+    }
+}
+
+fn main() {}
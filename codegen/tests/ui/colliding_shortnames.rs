@@ -0,0 +1,29 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[action(shortname = 0x01)]
+    pub fn increment(_ctx: ContractContext, state: CounterState) -> CounterState {
+        CounterState {
+            count: state.count + 1,
+        }
+    }
+
+    #[view(shortname = 0x01)]
+    pub fn count(_ctx: ContractContext, state: &CounterState) -> u64 {
+        state.count
+    }
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[contract]
+    impl CounterState {
+        #[action]
+        pub fn increment(_ctx: ContractContext, count: u64) -> CounterState {
+            CounterState { count: count + 1 }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+#[contract]
+mod counter {
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: pbc_contract_common::context::ContractContext) -> CounterState {
+        CounterState { count: 0 }
+    }
+}
+
+fn main() {}
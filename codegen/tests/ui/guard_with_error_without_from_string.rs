@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[action(only_owner)]
+    pub fn increment(_ctx: ContractContext, state: &mut CounterState) -> Result<(), u64> {
+        state.count += 1;
+        Ok(())
+    }
+}
+
+fn main() {}
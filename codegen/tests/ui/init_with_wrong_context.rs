@@ -0,0 +1,18 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+#[contract]
+mod counter {
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: u64) -> CounterState {
+        CounterState { count: 0 }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[action]
+    pub fn increment(_ctx: ContractContext, state: &mut CounterState) -> CounterState {
+        state.count += 1;
+        state.clone()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+#[contract]
+mod counter {
+    #[state]
+    pub struct CounterState {
+        count: u64,
+        enabled: bool,
+    }
+}
+
+fn main() {}
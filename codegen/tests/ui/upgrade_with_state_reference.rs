@@ -0,0 +1,34 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state(previous)]
+    pub struct CounterStateV1 {
+        count: u32,
+    }
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> CounterState {
+        CounterState { count: 0 }
+    }
+
+    #[upgrade]
+    pub fn migrate(_ctx: ContractContext, old_state: &CounterStateV1) -> CounterState {
+        CounterState {
+            count: old_state.count as u64,
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+use pbc_contract_common::context::ContractContext;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        count: u64,
+    }
+
+    #[view]
+    pub fn count(_ctx: ContractContext, _state: CounterState) {}
+}
+
+fn main() {}
@@ -0,0 +1,96 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use pbc_contract_common::abi::compatibility::check_compatibility;
+use pbc_contract_common::abi::{ContractAbi, SchemaAbi};
+use pbc_contract_common::context::ContractContext;
+
+use common::{context, error_of, exported_abi, invoke, state_of};
+
+#[contract]
+mod supply {
+    use super::*;
+
+    #[state(previous)]
+    pub struct SupplyStateV1 {
+        pub supply: u64,
+    }
+
+    #[state]
+    pub struct SupplyState {
+        pub supply: u64,
+        pub burned: u64,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> SupplyState {
+        SupplyState {
+            supply: 0,
+            burned: 0,
+        }
+    }
+
+    #[upgrade]
+    pub fn migrate(_ctx: ContractContext, old_state: SupplyStateV1) -> Result<SupplyState, String> {
+        if old_state.supply == u64::MAX {
+            return Err(String::from("Supply cannot be migrated"));
+        }
+        Ok(SupplyState {
+            supply: old_state.supply,
+            burned: 0,
+        })
+    }
+}
+
+use supply::*;
+
+fn upgrade_from(old_state: SupplyStateV1) -> Vec<u8> {
+    invoke(upgrade, &context(), &old_state, &[])
+}
+
+#[test]
+fn upgrade_reads_the_previous_state_and_returns_the_new_one() {
+    let result = upgrade_from(SupplyStateV1 { supply: 7 });
+
+    assert_eq!(
+        state_of::<SupplyState>(&result),
+        SupplyState {
+            supply: 7,
+            burned: 0,
+        }
+    );
+}
+
+#[test]
+fn upgrade_can_fail() {
+    let result = upgrade_from(SupplyStateV1 { supply: u64::MAX });
+
+    assert_eq!(error_of::<String>(&result), "Supply cannot be migrated");
+}
+
+#[test]
+fn abi_records_the_old_and_new_state() {
+    let abi = exported_abi(export_contract_schema_json);
+    let upgrade = abi.upgrade.as_ref().unwrap();
+
+    assert_eq!(upgrade.name, "migrate");
+    assert_eq!(upgrade.old_state, SchemaAbi::of::<SupplyStateV1>());
+    assert_eq!(upgrade.new_state, SchemaAbi::of::<SupplyState>());
+    assert_eq!(upgrade.error, Some(SchemaAbi::of::<String>()));
+    assert!(abi.functions.iter().all(|f| f.name != "migrate"));
+}
+
+#[test]
+fn upgrade_is_compatible_with_the_previous_version() {
+    let new = exported_abi(export_contract_schema_json);
+    let old = ContractAbi {
+        state: SchemaAbi::of::<SupplyStateV1>(),
+        upgrade: None,
+        ..new.clone()
+    };
+
+    assert_eq!(check_compatibility(&old, &new), vec![]);
+}
@@ -0,0 +1,104 @@
+#[macro_use]
+extern crate pbc_contract_codegen;
+extern crate pbc_contract_common;
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::result::STATUS_OK;
+use pbc_contract_common::serialization::ReadWrite;
+
+use common::{bytes_of, context, error_of, invoke, value_of};
+
+#[contract]
+mod ledger {
+    use super::*;
+
+    #[state]
+    pub struct LedgerState {
+        pub balances: BTreeMap<Address, u64>,
+    }
+
+    #[init]
+    pub fn initialize(_ctx: ContractContext) -> LedgerState {
+        LedgerState {
+            balances: BTreeMap::new(),
+        }
+    }
+
+    #[view(shortname = 0x05)]
+    pub fn balance_of(_ctx: ContractContext, state: LedgerState, owner: Address) -> u64 {
+        *state.balances.get(&owner).unwrap_or(&0)
+    }
+}
+
+use ledger::*;
+
+#[test]
+fn view_export_returns_the_value_without_a_new_state() {
+    let holder = Address::Account([7u8; 20]);
+    let mut balances = BTreeMap::new();
+    balances.insert(holder, 42u64);
+    let state = LedgerState { balances };
+
+    let result = invoke(view_5, &context(), &state, &bytes_of(&holder));
+
+    assert_eq!(value_of::<u64>(&result), 42);
+    // Only the value follows the status, so the host keeps the state and no events are emitted.
+    let mut expected = vec![STATUS_OK];
+    bytes_of(&42u64).write_to(&mut expected).unwrap();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn view_export_defaults_missing_balances() {
+    let state = LedgerState {
+        balances: BTreeMap::new(),
+    };
+
+    let result = invoke(
+        view_5,
+        &context(),
+        &state,
+        &bytes_of(&Address::Account([7u8; 20])),
+    );
+
+    assert_eq!(value_of::<u64>(&result), 0);
+}
+
+#[test]
+fn view_dispatcher_calls_the_view_of_the_shortname() {
+    let holder = Address::Account([7u8; 20]);
+    let mut balances = BTreeMap::new();
+    balances.insert(holder, 42u64);
+    let state = LedgerState { balances };
+    let mut rpc = vec![0, 0, 0, 5];
+    holder.write_to(&mut rpc).unwrap();
+
+    let result = invoke(view, &context(), &state, &rpc);
+    let unknown = invoke(view, &context(), &state, &[0, 0, 0, 6]);
+
+    assert_eq!(value_of::<u64>(&result), 42);
+    assert_eq!(
+        error_of::<String>(&unknown),
+        "Unknown view shortname 0x00000006"
+    );
+}
+
+#[test]
+fn abi_lists_views_with_arguments_and_return_type() {
+    let mut buffer = vec![0u8; 4096];
+    let len = export_contract_schema_json(buffer.as_mut_ptr(), buffer.len());
+    let abi: serde_json::Value = serde_json::from_slice(&buffer[..len]).unwrap();
+
+    let functions = abi["functions"].as_array().unwrap();
+    let view = functions.iter().find(|f| f["kind"] == "view").unwrap();
+    assert_eq!(view["kind"], "view");
+    assert_eq!(view["name"], "balance_of");
+    assert_eq!(view["arguments"][0]["name"], "owner");
+    assert_eq!(view["arguments"][0]["schema"]["type_name"], "Address");
+    assert_eq!(view["returns"]["type_name"], "u64");
+}
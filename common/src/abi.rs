@@ -0,0 +1,154 @@
+use reflection::{Member, Reflection, Schema, Type};
+use serde::{Deserialize, Serialize};
+use trees::Node;
+
+use crate::abi::binary::BinaryAbi;
+use crate::serialization::ReadWrite;
+
+pub mod binary;
+pub mod compatibility;
+
+/// Owned copy of a `reflection::Schema` tree, as it appears in the contract ABI.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchemaAbi {
+    Field {
+        name: String,
+        ty: Type,
+        type_name: Option<String>,
+        members: Vec<SchemaAbi>,
+    },
+    Variant {
+        name: String,
+        members: Vec<SchemaAbi>,
+    },
+}
+
+impl SchemaAbi {
+    /// The fully expanded schema of `T`.
+    pub fn of<T: Reflection>() -> Self {
+        SchemaAbi::from_schema(&T::schemata())
+    }
+
+    /// Owned copy of a schema tree produced by `reflection`.
+    pub fn from_schema(schema: &Schema) -> Self {
+        SchemaAbi::from_node(schema.root())
+    }
+
+    fn from_node(node: &Node<Member>) -> Self {
+        let members = node.iter().map(SchemaAbi::from_node).collect();
+        match node.data() {
+            Member::Field(field) => SchemaAbi::Field {
+                name: field.id.to_string(),
+                ty: field.ty,
+                type_name: field.tyname.clone(),
+                members,
+            },
+            Member::Variant(variant) => SchemaAbi::Variant {
+                name: variant.id.to_string(),
+                members,
+            },
+        }
+    }
+}
+
+/// An event type that the contract may emit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventAbi {
+    pub name: String,
+    pub event_id: u32,
+    pub schema: SchemaAbi,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionKind {
+    Init,
+    Action,
+    View,
+}
+
+/// A named argument of a contract function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArgumentAbi {
+    pub name: String,
+    pub schema: SchemaAbi,
+}
+
+/// A function that can be invoked on the contract.
+///
+/// Actions and views are identified by their shortname, while the initializer has none.
+/// `returns` is the value returned by a view, and `error` is the error of a function returning
+/// `Result`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionAbi {
+    pub kind: FunctionKind,
+    pub name: String,
+    pub shortname: Option<u32>,
+    pub arguments: Vec<ArgumentAbi>,
+    pub returns: Option<SchemaAbi>,
+    pub error: Option<SchemaAbi>,
+}
+
+/// The `#[upgrade]` function migrating the state of the previous version of the contract.
+///
+/// `old_state` is the layout the migration reads the stored state with, and `new_state` the
+/// layout it produces, which is the state of this contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeAbi {
+    pub name: String,
+    pub old_state: SchemaAbi,
+    pub new_state: SchemaAbi,
+    pub error: Option<SchemaAbi>,
+}
+
+/// Version of the ABI format produced by this crate.
+pub const ABI_VERSION: u32 = 2;
+
+/// The ABI of a contract, exported as json by `export_contract_schema_json` and in the compact
+/// [`binary`] format by `export_contract_schema_binary`.
+///
+/// The exports write the ABI into the buffer supplied by the host and return its length. If
+/// the buffer is too small nothing is written, so the host may call them with an empty buffer to
+/// learn the size to allocate.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractAbi {
+    pub version: u32,
+    pub state: SchemaAbi,
+    pub functions: Vec<FunctionAbi>,
+    pub events: Vec<EventAbi>,
+    pub upgrade: Option<UpgradeAbi>,
+}
+
+impl ContractAbi {
+    pub fn as_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn as_binary(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        BinaryAbi::from(self).write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Panics if `bytes` is not a binary ABI, see [`try_from_binary`](Self::try_from_binary).
+    pub fn from_binary(bytes: &[u8]) -> Self {
+        Self::try_from_binary(bytes).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Read the ABI from `bytes`, failing if they are not exactly a well-formed binary ABI.
+    pub fn try_from_binary(mut bytes: &[u8]) -> Result<Self, String> {
+        let binary = BinaryAbi::decode(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(format!(
+                "The ABI is followed by {} unexpected bytes",
+                bytes.len()
+            ));
+        }
+        binary.try_to_contract_abi()
+    }
+}
@@ -0,0 +1,862 @@
+//! Compact binary encoding of a [`ContractAbi`].
+//!
+//! Every distinct type of the ABI is stored once in a type table, and schemas refer to the types
+//! by their index in the table. All integers are big-endian and strings are length-prefixed, as
+//! elsewhere in the contract serialization.
+//!
+//! ```text
+//! abi      := version:u32 types:list<type> state:member functions:list<function> events:list<event>
+//!             upgrade:option<upgrade>
+//! type     := ty:u8 type_name:option<string> members:list<member>
+//! member   := 0 name:string type_index:u32
+//!           | 1 name:string members:list<member>
+//! function := kind:u8 name:string shortname:option<u32> arguments:list<argument>
+//!             returns:option<member> error:option<member>
+//! argument := name:string schema:member
+//! event    := name:string event_id:u32 schema:member
+//! upgrade  := name:string old_state:member new_state:member error:option<member>
+//! ```
+//!
+//! `list<T>` is a `u32` count followed by the elements, and `option<T>` is a `0` byte or a `1`
+//! byte followed by the value.
+//!
+//! The binary ABI is read from untrusted modules, so [`BinaryAbi::decode`] and
+//! [`BinaryAbi::try_to_contract_abi`] return an error on malformed input where `read_from` and
+//! [`BinaryAbi::to_contract_abi`] panic. Both bound the nesting of schemas, and the expansion
+//! also rejects types referring to themselves and tables expanding to too many schemas.
+
+use std::io::{Read, Write};
+
+use reflection::Type;
+
+use crate::abi::{
+    ArgumentAbi, ContractAbi, EventAbi, FunctionAbi, FunctionKind, SchemaAbi, UpgradeAbi,
+};
+use crate::serialization::{ReadWrite, WriteInt};
+
+/// Every `reflection::Type`, indexed by the byte it is encoded as.
+const TYPES: [Type; 35] = [
+    Type::Unknown,
+    Type::Struct,
+    Type::Enum,
+    Type::Bool,
+    Type::I8,
+    Type::U8,
+    Type::I16,
+    Type::U16,
+    Type::I32,
+    Type::U32,
+    Type::I64,
+    Type::U64,
+    Type::I128,
+    Type::U128,
+    Type::F32,
+    Type::F64,
+    Type::Range,
+    Type::RefStr,
+    Type::String,
+    Type::Array,
+    Type::Tuple,
+    Type::Vec,
+    Type::CPtr,
+    Type::Ptr,
+    Type::NonNull,
+    Type::Ref,
+    Type::RefMut,
+    Type::Box,
+    Type::Rc,
+    Type::Option,
+    Type::Result,
+    Type::BTreeSet,
+    Type::HashSet,
+    Type::BTreeMap,
+    Type::HashMap,
+];
+
+/// A type in the type table: a schema node without its name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeEntry {
+    pub ty: Type,
+    pub type_name: Option<String>,
+    pub members: Vec<MemberEntry>,
+}
+
+/// A named reference to a type in the table, or an enum variant with its fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemberEntry {
+    Field {
+        name: String,
+        type_index: u32,
+    },
+    Variant {
+        name: String,
+        members: Vec<MemberEntry>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArgumentEntry {
+    pub name: String,
+    pub schema: MemberEntry,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionEntry {
+    pub kind: FunctionKind,
+    pub name: String,
+    pub shortname: Option<u32>,
+    pub arguments: Vec<ArgumentEntry>,
+    pub returns: Option<MemberEntry>,
+    pub error: Option<MemberEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventEntry {
+    pub name: String,
+    pub event_id: u32,
+    pub schema: MemberEntry,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpgradeEntry {
+    pub name: String,
+    pub old_state: MemberEntry,
+    pub new_state: MemberEntry,
+    pub error: Option<MemberEntry>,
+}
+
+/// The binary ABI of a contract, exported by `export_contract_schema_binary`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BinaryAbi {
+    pub version: u32,
+    pub types: Vec<TypeEntry>,
+    pub state: MemberEntry,
+    pub functions: Vec<FunctionEntry>,
+    pub events: Vec<EventEntry>,
+    pub upgrade: Option<UpgradeEntry>,
+}
+
+impl From<&ContractAbi> for BinaryAbi {
+    fn from(abi: &ContractAbi) -> Self {
+        let mut types = Vec::new();
+        let state = intern(&abi.state, &mut types);
+        let functions = abi
+            .functions
+            .iter()
+            .map(|function| FunctionEntry {
+                kind: function.kind,
+                name: function.name.clone(),
+                shortname: function.shortname,
+                arguments: function
+                    .arguments
+                    .iter()
+                    .map(|argument| ArgumentEntry {
+                        name: argument.name.clone(),
+                        schema: intern(&argument.schema, &mut types),
+                    })
+                    .collect(),
+                returns: function.returns.as_ref().map(|s| intern(s, &mut types)),
+                error: function.error.as_ref().map(|s| intern(s, &mut types)),
+            })
+            .collect();
+        let events = abi
+            .events
+            .iter()
+            .map(|event| EventEntry {
+                name: event.name.clone(),
+                event_id: event.event_id,
+                schema: intern(&event.schema, &mut types),
+            })
+            .collect();
+        let upgrade = abi.upgrade.as_ref().map(|upgrade| UpgradeEntry {
+            name: upgrade.name.clone(),
+            old_state: intern(&upgrade.old_state, &mut types),
+            new_state: intern(&upgrade.new_state, &mut types),
+            error: upgrade.error.as_ref().map(|s| intern(s, &mut types)),
+        });
+
+        BinaryAbi {
+            version: abi.version,
+            types,
+            state,
+            functions,
+            events,
+            upgrade,
+        }
+    }
+}
+
+/// Add the types of `schema` to `types`, reusing equal entries, and return its member entry.
+fn intern(schema: &SchemaAbi, types: &mut Vec<TypeEntry>) -> MemberEntry {
+    match schema {
+        SchemaAbi::Field {
+            name,
+            ty,
+            type_name,
+            members,
+        } => {
+            let entry = TypeEntry {
+                ty: *ty,
+                type_name: type_name.clone(),
+                members: members.iter().map(|m| intern(m, types)).collect(),
+            };
+            let type_index = match types.iter().position(|existing| *existing == entry) {
+                Some(index) => index,
+                None => {
+                    types.push(entry);
+                    types.len() - 1
+                }
+            };
+            MemberEntry::Field {
+                name: name.clone(),
+                type_index: type_index as u32,
+            }
+        }
+        SchemaAbi::Variant { name, members } => MemberEntry::Variant {
+            name: name.clone(),
+            members: members.iter().map(|m| intern(m, types)).collect(),
+        },
+    }
+}
+
+impl BinaryAbi {
+    /// Read the binary ABI from `reader`, or fail if it is malformed.
+    pub fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        Ok(BinaryAbi {
+            version: decode_u32(reader)?,
+            types: decode_list(reader, TypeEntry::decode)?,
+            state: MemberEntry::decode(reader)?,
+            functions: decode_list(reader, FunctionEntry::decode)?,
+            events: decode_list(reader, EventEntry::decode)?,
+            upgrade: decode_option(reader, UpgradeEntry::decode)?,
+        })
+    }
+
+    /// Expand the type table back into the json representation of the ABI, or fail if a type
+    /// index is outside the type table, a type refers to itself, or the expanded schemas exceed
+    /// [`MAX_SCHEMA_DEPTH`] or [`MAX_SCHEMA_NODES`].
+    pub fn try_to_contract_abi(&self) -> Result<ContractAbi, String> {
+        let mut expansion = Expansion {
+            abi: self,
+            types: Vec::new(),
+            nodes: 0,
+        };
+        let mut functions = Vec::new();
+        for function in &self.functions {
+            let mut arguments = Vec::new();
+            for argument in &function.arguments {
+                arguments.push(ArgumentAbi {
+                    name: argument.name.clone(),
+                    schema: expansion.expand(&argument.schema, 0)?,
+                });
+            }
+            functions.push(FunctionAbi {
+                kind: function.kind,
+                name: function.name.clone(),
+                shortname: function.shortname,
+                arguments,
+                returns: expansion.expand_option(&function.returns)?,
+                error: expansion.expand_option(&function.error)?,
+            });
+        }
+        let mut events = Vec::new();
+        for event in &self.events {
+            events.push(EventAbi {
+                name: event.name.clone(),
+                event_id: event.event_id,
+                schema: expansion.expand(&event.schema, 0)?,
+            });
+        }
+        let upgrade = match &self.upgrade {
+            None => None,
+            Some(upgrade) => Some(UpgradeAbi {
+                name: upgrade.name.clone(),
+                old_state: expansion.expand(&upgrade.old_state, 0)?,
+                new_state: expansion.expand(&upgrade.new_state, 0)?,
+                error: expansion.expand_option(&upgrade.error)?,
+            }),
+        };
+
+        Ok(ContractAbi {
+            version: self.version,
+            state: expansion.expand(&self.state, 0)?,
+            functions,
+            events,
+            upgrade,
+        })
+    }
+
+    /// Expand the type table back into the json representation of the ABI.
+    ///
+    /// Panics if the type table cannot be expanded, see
+    /// [`try_to_contract_abi`](Self::try_to_contract_abi).
+    pub fn to_contract_abi(&self) -> ContractAbi {
+        self.try_to_contract_abi()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+/// The deepest nesting of schemas in a binary ABI, counting both types and enum variants.
+pub const MAX_SCHEMA_DEPTH: usize = 64;
+
+/// The most schema nodes a binary ABI may expand to. Types are stored once in the table but
+/// expanded wherever they are referred to, so a small table may expand to a huge ABI.
+pub const MAX_SCHEMA_NODES: usize = 100_000;
+
+/// An expansion of the type table of a binary ABI into schemas, within the limits.
+struct Expansion<'a> {
+    abi: &'a BinaryAbi,
+    /// The indices of the types being expanded, outermost first.
+    types: Vec<u32>,
+    /// The schema nodes expanded so far.
+    nodes: usize,
+}
+
+impl Expansion<'_> {
+    /// The schema of `member`, nested `depth` levels deep.
+    fn expand(&mut self, member: &MemberEntry, depth: usize) -> Result<SchemaAbi, String> {
+        self.nodes += 1;
+        if self.nodes > MAX_SCHEMA_NODES {
+            return Err(format!(
+                "The ABI expands to more than {} schemas",
+                MAX_SCHEMA_NODES
+            ));
+        }
+        if depth >= MAX_SCHEMA_DEPTH {
+            return Err(format!(
+                "The ABI nests schemas deeper than {}",
+                MAX_SCHEMA_DEPTH
+            ));
+        }
+
+        match member {
+            MemberEntry::Field { name, type_index } => {
+                let entry = self
+                    .abi
+                    .types
+                    .get(*type_index as usize)
+                    .ok_or_else(|| format!("Type index {} is out of bounds", type_index))?;
+                if self.types.contains(type_index) {
+                    return Err(format!("Type index {} refers to itself", type_index));
+                }
+                self.types.push(*type_index);
+                let members = self.expand_all(&entry.members, depth)?;
+                self.types.pop();
+                Ok(SchemaAbi::Field {
+                    name: name.clone(),
+                    ty: entry.ty,
+                    type_name: entry.type_name.clone(),
+                    members,
+                })
+            }
+            MemberEntry::Variant { name, members } => Ok(SchemaAbi::Variant {
+                name: name.clone(),
+                members: self.expand_all(members, depth)?,
+            }),
+        }
+    }
+
+    /// The schemas of the members of a schema nested `depth` levels deep.
+    fn expand_all(
+        &mut self,
+        members: &[MemberEntry],
+        depth: usize,
+    ) -> Result<Vec<SchemaAbi>, String> {
+        members
+            .iter()
+            .map(|member| self.expand(member, depth + 1))
+            .collect()
+    }
+
+    fn expand_option(&mut self, member: &Option<MemberEntry>) -> Result<Option<SchemaAbi>, String> {
+        member
+            .as_ref()
+            .map(|member| self.expand(member, 0))
+            .transpose()
+    }
+}
+
+/// The entries of the binary ABI, read without panicking.
+trait Decode: Sized {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String>;
+}
+
+/// The value of `result`, panicking with its error as `ReadWrite::read_from` does.
+fn decoded<S>(result: Result<S, String>) -> S {
+    result.unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn decode_byte<R: Read>(reader: &mut R) -> Result<u8, String> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte).map_err(|_| truncated())?;
+    Ok(byte[0])
+}
+
+fn decode_u32<R: Read>(reader: &mut R) -> Result<u32, String> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes).map_err(|_| truncated())?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// A string, whose bytes are only allocated as they are read.
+fn decode_string<R: Read>(reader: &mut R) -> Result<String, String> {
+    let len = decode_u32(reader)?;
+    let mut bytes = Vec::new();
+    reader
+        .take(len.into())
+        .read_to_end(&mut bytes)
+        .map_err(|error| error.to_string())?;
+    if bytes.len() != len as usize {
+        return Err(truncated());
+    }
+    String::from_utf8(bytes).map_err(|_| String::from("A name of the ABI is not valid UTF-8"))
+}
+
+fn decode_list<R: Read, S>(
+    reader: &mut R,
+    decode: fn(&mut R) -> Result<S, String>,
+) -> Result<Vec<S>, String> {
+    let len = decode_u32(reader)?;
+    (0..len).map(|_| decode(reader)).collect()
+}
+
+fn decode_option<R: Read, S>(
+    reader: &mut R,
+    decode: fn(&mut R) -> Result<S, String>,
+) -> Result<Option<S>, String> {
+    match decode_byte(reader)? {
+        0 => Ok(None),
+        _ => decode(reader).map(Some),
+    }
+}
+
+fn truncated() -> String {
+    String::from("The ABI ends unexpectedly")
+}
+
+fn write_list<T: Write, S: ReadWrite>(writer: &mut T, items: &[S]) -> std::io::Result<()> {
+    writer.write_u32_be(items.len() as u32)?;
+    for item in items {
+        item.write_to(writer)?;
+    }
+    Ok(())
+}
+
+fn write_option_u32<T: Write>(writer: &mut T, value: Option<u32>) -> std::io::Result<()> {
+    match value {
+        None => writer.write_byte(0),
+        Some(value) => {
+            writer.write_byte(1)?;
+            writer.write_u32_be(value)
+        }
+    }
+}
+
+impl Decode for TypeEntry {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        let ty = decode_byte(reader)?;
+        Ok(TypeEntry {
+            ty: *TYPES
+                .get(ty as usize)
+                .ok_or_else(|| format!("Unknown type {}", ty))?,
+            type_name: decode_option(reader, decode_string)?,
+            members: decode_list(reader, MemberEntry::decode)?,
+        })
+    }
+}
+
+impl ReadWrite for TypeEntry {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_byte(self.ty as u8)?;
+        self.type_name.write_to(writer)?;
+        write_list(writer, &self.members)
+    }
+}
+
+impl Decode for MemberEntry {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        decode_member(reader, 0)
+    }
+}
+
+/// A member nested `depth` variants deep, as variants hold their members inline.
+fn decode_member<R: Read>(reader: &mut R, depth: usize) -> Result<MemberEntry, String> {
+    if depth >= MAX_SCHEMA_DEPTH {
+        return Err(format!(
+            "The ABI nests schemas deeper than {}",
+            MAX_SCHEMA_DEPTH
+        ));
+    }
+    match decode_byte(reader)? {
+        0 => Ok(MemberEntry::Field {
+            name: decode_string(reader)?,
+            type_index: decode_u32(reader)?,
+        }),
+        1 => {
+            let name = decode_string(reader)?;
+            let len = decode_u32(reader)?;
+            let members = (0..len)
+                .map(|_| decode_member(reader, depth + 1))
+                .collect::<Result<_, _>>()?;
+            Ok(MemberEntry::Variant { name, members })
+        }
+        tag => Err(format!("Unknown member tag {}", tag)),
+    }
+}
+
+impl ReadWrite for MemberEntry {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        match self {
+            MemberEntry::Field { name, type_index } => {
+                writer.write_byte(0)?;
+                name.write_to(writer)?;
+                writer.write_u32_be(*type_index)
+            }
+            MemberEntry::Variant { name, members } => {
+                writer.write_byte(1)?;
+                name.write_to(writer)?;
+                write_list(writer, members)
+            }
+        }
+    }
+}
+
+impl Decode for FunctionKind {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        match decode_byte(reader)? {
+            0 => Ok(FunctionKind::Init),
+            1 => Ok(FunctionKind::Action),
+            2 => Ok(FunctionKind::View),
+            kind => Err(format!("Unknown function kind {}", kind)),
+        }
+    }
+}
+
+impl ReadWrite for FunctionKind {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_byte(*self as u8)
+    }
+}
+
+impl Decode for ArgumentEntry {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        Ok(ArgumentEntry {
+            name: decode_string(reader)?,
+            schema: MemberEntry::decode(reader)?,
+        })
+    }
+}
+
+impl ReadWrite for ArgumentEntry {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.name.write_to(writer)?;
+        self.schema.write_to(writer)
+    }
+}
+
+impl Decode for FunctionEntry {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        Ok(FunctionEntry {
+            kind: FunctionKind::decode(reader)?,
+            name: decode_string(reader)?,
+            shortname: decode_option(reader, decode_u32)?,
+            arguments: decode_list(reader, ArgumentEntry::decode)?,
+            returns: decode_option(reader, MemberEntry::decode)?,
+            error: decode_option(reader, MemberEntry::decode)?,
+        })
+    }
+}
+
+impl ReadWrite for FunctionEntry {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.kind.write_to(writer)?;
+        self.name.write_to(writer)?;
+        write_option_u32(writer, self.shortname)?;
+        write_list(writer, &self.arguments)?;
+        self.returns.write_to(writer)?;
+        self.error.write_to(writer)
+    }
+}
+
+impl Decode for EventEntry {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        Ok(EventEntry {
+            name: decode_string(reader)?,
+            event_id: decode_u32(reader)?,
+            schema: MemberEntry::decode(reader)?,
+        })
+    }
+}
+
+impl ReadWrite for EventEntry {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.name.write_to(writer)?;
+        writer.write_u32_be(self.event_id)?;
+        self.schema.write_to(writer)
+    }
+}
+
+impl Decode for UpgradeEntry {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, String> {
+        Ok(UpgradeEntry {
+            name: decode_string(reader)?,
+            old_state: MemberEntry::decode(reader)?,
+            new_state: MemberEntry::decode(reader)?,
+            error: decode_option(reader, MemberEntry::decode)?,
+        })
+    }
+}
+
+impl ReadWrite for UpgradeEntry {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.name.write_to(writer)?;
+        self.old_state.write_to(writer)?;
+        self.new_state.write_to(writer)?;
+        self.error.write_to(writer)
+    }
+}
+
+impl ReadWrite for BinaryAbi {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        decoded(Self::decode(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_u32_be(self.version)?;
+        write_list(writer, &self.types)?;
+        self.state.write_to(writer)?;
+        write_list(writer, &self.functions)?;
+        write_list(writer, &self.events)?;
+        self.upgrade.write_to(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::abi::ABI_VERSION;
+    use crate::address::Address;
+
+    fn sample_abi() -> ContractAbi {
+        ContractAbi {
+            version: ABI_VERSION,
+            state: SchemaAbi::of::<BTreeMap<Address, u64>>(),
+            functions: vec![
+                FunctionAbi {
+                    kind: FunctionKind::Init,
+                    name: String::from("initialize"),
+                    shortname: None,
+                    arguments: vec![],
+                    returns: None,
+                    error: None,
+                },
+                FunctionAbi {
+                    kind: FunctionKind::Action,
+                    name: String::from("transfer"),
+                    shortname: Some(0x01),
+                    arguments: vec![
+                        ArgumentAbi {
+                            name: String::from("to"),
+                            schema: SchemaAbi::of::<Address>(),
+                        },
+                        ArgumentAbi {
+                            name: String::from("amount"),
+                            schema: SchemaAbi::of::<u64>(),
+                        },
+                    ],
+                    returns: None,
+                    error: Some(SchemaAbi::of::<String>()),
+                },
+            ],
+            events: vec![],
+            upgrade: Some(UpgradeAbi {
+                name: String::from("migrate"),
+                old_state: SchemaAbi::of::<Vec<Address>>(),
+                new_state: SchemaAbi::of::<BTreeMap<Address, u64>>(),
+                error: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn binary_abi_round_trips_to_json_abi() {
+        let abi = sample_abi();
+
+        let bytes = abi.as_binary();
+        let decoded = ContractAbi::from_binary(&bytes);
+
+        assert_eq!(decoded, abi);
+    }
+
+    #[test]
+    fn malformed_binary_abis_are_rejected() {
+        let bytes = sample_abi().as_binary();
+
+        let truncated = ContractAbi::try_from_binary(&bytes[..bytes.len() - 1]);
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        let mut binary = BinaryAbi::from(&sample_abi());
+        binary.state = MemberEntry::Field {
+            name: String::new(),
+            type_index: 1000,
+        };
+        let mut out_of_bounds = Vec::new();
+        binary.write_to(&mut out_of_bounds).unwrap();
+
+        assert_eq!(truncated, Err(String::from("The ABI ends unexpectedly")));
+        assert_eq!(
+            ContractAbi::try_from_binary(&trailing),
+            Err(String::from("The ABI is followed by 1 unexpected bytes"))
+        );
+        assert_eq!(
+            ContractAbi::try_from_binary(&out_of_bounds),
+            Err(String::from("Type index 1000 is out of bounds"))
+        );
+        assert!(ContractAbi::try_from_binary(&[0xff; 64]).is_err());
+    }
+
+    fn field(type_index: u32) -> MemberEntry {
+        MemberEntry::Field {
+            name: String::new(),
+            type_index,
+        }
+    }
+
+    fn with_types(types: Vec<TypeEntry>) -> BinaryAbi {
+        BinaryAbi {
+            version: ABI_VERSION,
+            state: field(types.len() as u32 - 1),
+            types,
+            functions: Vec::new(),
+            events: Vec::new(),
+            upgrade: None,
+        }
+    }
+
+    fn vec_of(type_index: u32) -> TypeEntry {
+        TypeEntry {
+            ty: Type::Vec,
+            type_name: None,
+            members: vec![field(type_index)],
+        }
+    }
+
+    #[test]
+    fn recursive_types_are_rejected() {
+        let binary = with_types(vec![vec_of(0)]);
+
+        assert_eq!(
+            binary.try_to_contract_abi(),
+            Err(String::from("Type index 0 refers to itself"))
+        );
+    }
+
+    #[test]
+    fn deeply_nested_types_are_rejected() {
+        let mut types = vec![TypeEntry {
+            ty: Type::U8,
+            type_name: None,
+            members: Vec::new(),
+        }];
+        types.extend((0..MAX_SCHEMA_DEPTH as u32).map(vec_of));
+        let binary = with_types(types);
+
+        assert_eq!(
+            binary.try_to_contract_abi(),
+            Err(format!(
+                "The ABI nests schemas deeper than {}",
+                MAX_SCHEMA_DEPTH
+            ))
+        );
+    }
+
+    #[test]
+    fn exponentially_expanding_types_are_rejected() {
+        let mut types = vec![TypeEntry {
+            ty: Type::U8,
+            type_name: None,
+            members: Vec::new(),
+        }];
+        types.extend((0..40).map(|index| TypeEntry {
+            ty: Type::BTreeMap,
+            type_name: None,
+            members: vec![field(index), field(index)],
+        }));
+        let binary = with_types(types);
+
+        assert_eq!(
+            binary.try_to_contract_abi(),
+            Err(format!(
+                "The ABI expands to more than {} schemas",
+                MAX_SCHEMA_NODES
+            ))
+        );
+    }
+
+    #[test]
+    fn deeply_nested_variants_are_rejected() {
+        let mut bytes = Vec::new();
+        for _ in 0..=MAX_SCHEMA_DEPTH {
+            bytes.push(1);
+            "v".to_string().write_to(&mut bytes).unwrap();
+            bytes.write_u32_be(1).unwrap();
+        }
+
+        assert_eq!(
+            MemberEntry::decode(&mut bytes.as_slice()),
+            Err(format!(
+                "The ABI nests schemas deeper than {}",
+                MAX_SCHEMA_DEPTH
+            ))
+        );
+    }
+
+    #[test]
+    fn types_are_stored_once() {
+        let binary = BinaryAbi::from(&sample_abi());
+
+        let u64_types = binary
+            .types
+            .iter()
+            .filter(|entry| entry.ty == Type::U64)
+            .count();
+        assert_eq!(u64_types, 1);
+    }
+
+    #[test]
+    fn binary_abi_is_smaller_than_json() {
+        let abi = sample_abi();
+
+        assert!(abi.as_binary().len() < abi.as_json().len());
+    }
+}
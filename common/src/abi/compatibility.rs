@@ -0,0 +1,644 @@
+//! Detection of the changes between two versions of a contract that break deployed instances.
+//!
+//! State is serialized positionally, so the new version must lay out every state field and enum
+//! variant exactly like the old one to read the stored state. A new version with an `#[upgrade]`
+//! function reads the stored state with the previous layout declared by the migration instead,
+//! so that layout is checked against the old state. Invocations are encoded by
+//! shortname and argument position, so every old action and view must keep its kind, its
+//! shortname and the types of its arguments, and every view the type of the value it returns to
+//! its callers. The initializer only runs when a contract is deployed, so it may change freely.
+
+use std::fmt::{Display, Formatter};
+
+use reflection::Type;
+
+use crate::abi::{ContractAbi, FunctionAbi, FunctionKind, SchemaAbi};
+
+/// A change that makes the new version incompatible with the state or invocations of the old.
+///
+/// Paths are the dot-separated field names from the root of the schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BreakingChange {
+    RemovedField {
+        path: String,
+    },
+    AddedField {
+        path: String,
+    },
+    ReorderedField {
+        path: String,
+        old_index: usize,
+        new_index: usize,
+    },
+    ChangedType {
+        path: String,
+        old_type: String,
+        new_type: String,
+    },
+    RemovedVariant {
+        path: String,
+        variant: String,
+    },
+    ReorderedVariant {
+        path: String,
+        variant: String,
+        old_index: usize,
+        new_index: usize,
+    },
+    RemovedFunction {
+        name: String,
+    },
+    ChangedShortname {
+        name: String,
+        old_shortname: Option<u32>,
+        new_shortname: Option<u32>,
+    },
+    ChangedArguments {
+        name: String,
+        old_arguments: usize,
+        new_arguments: usize,
+    },
+    ChangedKind {
+        name: String,
+        old_kind: FunctionKind,
+        new_kind: FunctionKind,
+    },
+}
+
+impl Display for BreakingChange {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            BreakingChange::RemovedField { path } => write!(f, "Field `{}` was removed", path),
+            BreakingChange::AddedField { path } => write!(f, "Field `{}` was added", path),
+            BreakingChange::ReorderedField {
+                path,
+                old_index,
+                new_index,
+            } => write!(
+                f,
+                "Field `{}` moved from position {} to {}",
+                path, old_index, new_index
+            ),
+            BreakingChange::ChangedType {
+                path,
+                old_type,
+                new_type,
+            } => write!(
+                f,
+                "Type of `{}` changed from {} to {}",
+                path, old_type, new_type
+            ),
+            BreakingChange::RemovedVariant { path, variant } => {
+                write!(f, "Variant `{}` of `{}` was removed", variant, path)
+            }
+            BreakingChange::ReorderedVariant {
+                path,
+                variant,
+                old_index,
+                new_index,
+            } => write!(
+                f,
+                "Variant `{}` of `{}` moved from position {} to {}",
+                variant, path, old_index, new_index
+            ),
+            BreakingChange::RemovedFunction { name } => {
+                write!(f, "Function `{}` was removed", name)
+            }
+            BreakingChange::ChangedShortname {
+                name,
+                old_shortname,
+                new_shortname,
+            } => write!(
+                f,
+                "Shortname of `{}` changed from {:?} to {:?}",
+                name, old_shortname, new_shortname
+            ),
+            BreakingChange::ChangedArguments {
+                name,
+                old_arguments,
+                new_arguments,
+            } => write!(
+                f,
+                "`{}` takes {} arguments instead of {}",
+                name, new_arguments, old_arguments
+            ),
+            BreakingChange::ChangedKind {
+                name,
+                old_kind,
+                new_kind,
+            } => write!(
+                f,
+                "`{}` is now {} instead of {}",
+                name,
+                kind_name(*new_kind),
+                kind_name(*old_kind)
+            ),
+        }
+    }
+}
+
+/// The changes from `old` to `new` that break existing state or invocations.
+///
+/// Removed events, added functions and changes to the initializer do not break anything and are
+/// not reported.
+pub fn check_compatibility(old: &ContractAbi, new: &ContractAbi) -> Vec<BreakingChange> {
+    let mut changes = Vec::new();
+    let stored_state = match &new.upgrade {
+        Some(upgrade) => &upgrade.old_state,
+        None => &new.state,
+    };
+    compare_schema("state", &old.state, stored_state, &mut changes);
+    let invoked = old
+        .functions
+        .iter()
+        .filter(|function| function.kind != FunctionKind::Init);
+    for old_function in invoked {
+        match new.functions.iter().find(|f| f.name == old_function.name) {
+            Some(new_function) => compare_function(old_function, new_function, &mut changes),
+            None => changes.push(BreakingChange::RemovedFunction {
+                name: old_function.name.clone(),
+            }),
+        }
+    }
+    changes
+}
+
+/// The changes from `old` to `new` that prevent values of the old schema from being read with
+/// the new one.
+pub fn check_schema_compatibility(old: &SchemaAbi, new: &SchemaAbi) -> Vec<BreakingChange> {
+    let mut changes = Vec::new();
+    compare_schema(schema_name(old), old, new, &mut changes);
+    changes
+}
+
+fn compare_function(old: &FunctionAbi, new: &FunctionAbi, changes: &mut Vec<BreakingChange>) {
+    if old.kind != new.kind {
+        changes.push(BreakingChange::ChangedKind {
+            name: old.name.clone(),
+            old_kind: old.kind,
+            new_kind: new.kind,
+        });
+    }
+    if old.shortname != new.shortname {
+        changes.push(BreakingChange::ChangedShortname {
+            name: old.name.clone(),
+            old_shortname: old.shortname,
+            new_shortname: new.shortname,
+        });
+    }
+    if old.arguments.len() != new.arguments.len() {
+        changes.push(BreakingChange::ChangedArguments {
+            name: old.name.clone(),
+            old_arguments: old.arguments.len(),
+            new_arguments: new.arguments.len(),
+        });
+    }
+    for (old_argument, new_argument) in old.arguments.iter().zip(&new.arguments) {
+        let path = format!("{}.{}", old.name, old_argument.name);
+        compare_schema(&path, &old_argument.schema, &new_argument.schema, changes);
+    }
+    if old.kind == FunctionKind::View && new.kind == FunctionKind::View {
+        let path = format!("{}.return", old.name);
+        match (&old.returns, &new.returns) {
+            (Some(old_returns), Some(new_returns)) => {
+                compare_schema(&path, old_returns, new_returns, changes)
+            }
+            (None, None) => {}
+            (old_returns, new_returns) => changes.push(BreakingChange::ChangedType {
+                path,
+                old_type: old_returns.as_ref().map_or(String::from("()"), describe),
+                new_type: new_returns.as_ref().map_or(String::from("()"), describe),
+            }),
+        }
+    }
+}
+
+fn compare_schema(path: &str, old: &SchemaAbi, new: &SchemaAbi, changes: &mut Vec<BreakingChange>) {
+    let (old_ty, new_ty) = match (old, new) {
+        (SchemaAbi::Field { ty: old_ty, .. }, SchemaAbi::Field { ty: new_ty, .. }) => {
+            (*old_ty, *new_ty)
+        }
+        _ => {
+            changes.push(changed_type(path, old, new));
+            return;
+        }
+    };
+    // Structs and enums are compared by their members, so that renaming a type is not breaking.
+    let named_by_members = matches!(old_ty, Type::Struct | Type::Enum);
+    if old_ty != new_ty || (!named_by_members && type_name(old) != type_name(new)) {
+        changes.push(changed_type(path, old, new));
+        return;
+    }
+
+    match old_ty {
+        Type::Struct => compare_fields(path, members(old), members(new), changes),
+        Type::Enum => compare_variants(path, members(old), members(new), changes),
+        _ => {
+            if members(old).len() != members(new).len() {
+                changes.push(changed_type(path, old, new));
+                return;
+            }
+            for (old_member, new_member) in members(old).iter().zip(members(new)) {
+                compare_schema(path, old_member, new_member, changes);
+            }
+        }
+    }
+}
+
+fn compare_fields(
+    path: &str,
+    old: &[SchemaAbi],
+    new: &[SchemaAbi],
+    changes: &mut Vec<BreakingChange>,
+) {
+    for (old_index, old_field) in old.iter().enumerate() {
+        let field_path = format!("{}.{}", path, schema_name(old_field));
+        match position_of(new, schema_name(old_field)) {
+            Some(new_index) => {
+                if new_index != old_index {
+                    changes.push(BreakingChange::ReorderedField {
+                        path: field_path.clone(),
+                        old_index,
+                        new_index,
+                    });
+                }
+                compare_schema(&field_path, old_field, &new[new_index], changes);
+            }
+            None => changes.push(BreakingChange::RemovedField { path: field_path }),
+        }
+    }
+    for new_field in new {
+        if position_of(old, schema_name(new_field)).is_none() {
+            changes.push(BreakingChange::AddedField {
+                path: format!("{}.{}", path, schema_name(new_field)),
+            });
+        }
+    }
+}
+
+fn compare_variants(
+    path: &str,
+    old: &[SchemaAbi],
+    new: &[SchemaAbi],
+    changes: &mut Vec<BreakingChange>,
+) {
+    for (old_index, old_variant) in old.iter().enumerate() {
+        let variant = schema_name(old_variant).to_string();
+        match position_of(new, &variant) {
+            Some(new_index) => {
+                if new_index != old_index {
+                    changes.push(BreakingChange::ReorderedVariant {
+                        path: path.to_string(),
+                        variant: variant.clone(),
+                        old_index,
+                        new_index,
+                    });
+                }
+                let variant_path = format!("{}::{}", path, variant);
+                compare_fields(
+                    &variant_path,
+                    members(old_variant),
+                    members(&new[new_index]),
+                    changes,
+                );
+            }
+            None => changes.push(BreakingChange::RemovedVariant {
+                path: path.to_string(),
+                variant,
+            }),
+        }
+    }
+}
+
+fn changed_type(path: &str, old: &SchemaAbi, new: &SchemaAbi) -> BreakingChange {
+    BreakingChange::ChangedType {
+        path: path.to_string(),
+        old_type: describe(old),
+        new_type: describe(new),
+    }
+}
+
+fn describe(schema: &SchemaAbi) -> String {
+    match schema {
+        SchemaAbi::Field { ty, type_name, .. } => match type_name {
+            Some(type_name) => type_name.clone(),
+            None => ty.to_string(),
+        },
+        SchemaAbi::Variant { name, .. } => name.clone(),
+    }
+}
+
+fn kind_name(kind: FunctionKind) -> &'static str {
+    match kind {
+        FunctionKind::Init => "the initializer",
+        FunctionKind::Action => "an action",
+        FunctionKind::View => "a view",
+    }
+}
+
+fn position_of(schemas: &[SchemaAbi], name: &str) -> Option<usize> {
+    schemas
+        .iter()
+        .position(|schema| schema_name(schema) == name)
+}
+
+fn schema_name(schema: &SchemaAbi) -> &str {
+    match schema {
+        SchemaAbi::Field { name, .. } | SchemaAbi::Variant { name, .. } => name,
+    }
+}
+
+fn type_name(schema: &SchemaAbi) -> Option<&String> {
+    match schema {
+        SchemaAbi::Field { type_name, .. } => type_name.as_ref(),
+        SchemaAbi::Variant { .. } => None,
+    }
+}
+
+fn members(schema: &SchemaAbi) -> &[SchemaAbi] {
+    match schema {
+        SchemaAbi::Field { members, .. } | SchemaAbi::Variant { members, .. } => members,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::{ArgumentAbi, UpgradeAbi, ABI_VERSION};
+
+    fn field(name: &str, ty: Type, type_name: &str, members: Vec<SchemaAbi>) -> SchemaAbi {
+        SchemaAbi::Field {
+            name: name.to_string(),
+            ty,
+            type_name: Some(type_name.to_string()),
+            members,
+        }
+    }
+
+    fn state(fields: Vec<SchemaAbi>) -> SchemaAbi {
+        field("_", Type::Struct, "State", fields)
+    }
+
+    fn action(name: &str, shortname: u32, arguments: Vec<SchemaAbi>) -> FunctionAbi {
+        FunctionAbi {
+            kind: FunctionKind::Action,
+            shortname: Some(shortname),
+            ..init(name, arguments)
+        }
+    }
+
+    fn view(name: &str, shortname: u32, returns: SchemaAbi) -> FunctionAbi {
+        FunctionAbi {
+            kind: FunctionKind::View,
+            shortname: Some(shortname),
+            returns: Some(returns),
+            ..init(name, vec![])
+        }
+    }
+
+    fn init(name: &str, arguments: Vec<SchemaAbi>) -> FunctionAbi {
+        FunctionAbi {
+            kind: FunctionKind::Init,
+            name: name.to_string(),
+            shortname: None,
+            arguments: arguments
+                .into_iter()
+                .map(|schema| ArgumentAbi {
+                    name: schema_name(&schema).to_string(),
+                    schema,
+                })
+                .collect(),
+            returns: None,
+            error: None,
+        }
+    }
+
+    fn abi(state: SchemaAbi, functions: Vec<FunctionAbi>) -> ContractAbi {
+        ContractAbi {
+            version: ABI_VERSION,
+            state,
+            functions,
+            events: vec![],
+            upgrade: None,
+        }
+    }
+
+    #[test]
+    fn identical_abis_are_compatible() {
+        let old = abi(
+            state(vec![field("supply", Type::U64, "u64", vec![])]),
+            vec![action(
+                "mint",
+                1,
+                vec![field("amount", Type::U64, "u64", vec![])],
+            )],
+        );
+
+        assert_eq!(check_compatibility(&old, &old.clone()), vec![]);
+    }
+
+    #[test]
+    fn reports_removed_and_reordered_state_fields() {
+        let old = state(vec![
+            field("owner", Type::Struct, "Address", vec![]),
+            field("supply", Type::U64, "u64", vec![]),
+            field("name", Type::String, "String", vec![]),
+        ]);
+        let new = state(vec![
+            field("supply", Type::U64, "u64", vec![]),
+            field("owner", Type::Struct, "Address", vec![]),
+        ]);
+
+        assert_eq!(
+            check_schema_compatibility(&old, &new),
+            vec![
+                BreakingChange::ReorderedField {
+                    path: String::from("_.owner"),
+                    old_index: 0,
+                    new_index: 1,
+                },
+                BreakingChange::ReorderedField {
+                    path: String::from("_.supply"),
+                    old_index: 1,
+                    new_index: 0,
+                },
+                BreakingChange::RemovedField {
+                    path: String::from("_.name"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_removed_enum_variants() {
+        let variant = |name: &str| SchemaAbi::Variant {
+            name: name.to_string(),
+            members: vec![],
+        };
+        let old = field(
+            "_",
+            Type::Enum,
+            "Phase",
+            vec![variant("Open"), variant("Closed")],
+        );
+        let new = field("_", Type::Enum, "Phase", vec![variant("Open")]);
+
+        assert_eq!(
+            check_schema_compatibility(&old, &new),
+            vec![BreakingChange::RemovedVariant {
+                path: String::from("_"),
+                variant: String::from("Closed"),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_changed_arguments_and_shortnames() {
+        let old = abi(
+            state(vec![]),
+            vec![
+                action("mint", 1, vec![field("amount", Type::U64, "u64", vec![])]),
+                action("burn", 2, vec![]),
+            ],
+        );
+        let new = abi(
+            state(vec![]),
+            vec![
+                action("mint", 3, vec![field("amount", Type::U32, "u32", vec![])]),
+                action("burn_all", 2, vec![]),
+            ],
+        );
+
+        let changes = check_compatibility(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                BreakingChange::ChangedShortname {
+                    name: String::from("mint"),
+                    old_shortname: Some(1),
+                    new_shortname: Some(3),
+                },
+                BreakingChange::ChangedType {
+                    path: String::from("mint.amount"),
+                    old_type: String::from("u64"),
+                    new_type: String::from("u32"),
+                },
+                BreakingChange::RemovedFunction {
+                    name: String::from("burn"),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "Type of `mint.amount` changed from u64 to u32"
+        );
+    }
+
+    #[test]
+    fn reports_changed_function_kinds() {
+        let old = abi(
+            state(vec![]),
+            vec![
+                action("refresh", 1, vec![]),
+                view("supply", 2, field("_", Type::U64, "u64", vec![])),
+            ],
+        );
+        let mut refresh = view("refresh", 1, field("_", Type::U64, "u64", vec![]));
+        refresh.returns = None;
+        let new = abi(state(vec![]), vec![refresh, action("supply", 2, vec![])]);
+
+        let changes = check_compatibility(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                BreakingChange::ChangedKind {
+                    name: String::from("refresh"),
+                    old_kind: FunctionKind::Action,
+                    new_kind: FunctionKind::View,
+                },
+                BreakingChange::ChangedKind {
+                    name: String::from("supply"),
+                    old_kind: FunctionKind::View,
+                    new_kind: FunctionKind::Action,
+                },
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "`refresh` is now a view instead of an action"
+        );
+    }
+
+    #[test]
+    fn reports_changed_view_return_types() {
+        let old = abi(
+            state(vec![]),
+            vec![view("supply", 2, field("_", Type::U64, "u64", vec![]))],
+        );
+        let new = abi(
+            state(vec![]),
+            vec![view("supply", 2, field("_", Type::U32, "u32", vec![]))],
+        );
+
+        assert_eq!(
+            check_compatibility(&old, &new),
+            vec![BreakingChange::ChangedType {
+                path: String::from("supply.return"),
+                old_type: String::from("u64"),
+                new_type: String::from("u32"),
+            }]
+        );
+    }
+
+    #[test]
+    fn initializer_changes_are_not_breaking() {
+        let old = abi(
+            state(vec![]),
+            vec![init(
+                "initialize",
+                vec![field("supply", Type::U64, "u64", vec![])],
+            )],
+        );
+        let new = abi(
+            state(vec![]),
+            vec![init(
+                "initialize",
+                vec![
+                    field("symbol", Type::String, "String", vec![]),
+                    field("supply", Type::U32, "u32", vec![]),
+                ],
+            )],
+        );
+        let renamed = abi(state(vec![]), vec![init("create", vec![])]);
+
+        assert_eq!(check_compatibility(&old, &new), vec![]);
+        assert_eq!(check_compatibility(&old, &renamed), vec![]);
+    }
+
+    #[test]
+    fn state_changes_are_checked_against_the_upgrade() {
+        let old_state = state(vec![field("supply", Type::U64, "u64", vec![])]);
+        let new_state = state(vec![
+            field("supply", Type::U64, "u64", vec![]),
+            field("burned", Type::U64, "u64", vec![]),
+        ]);
+        let mut new = abi(new_state.clone(), vec![]);
+
+        assert_eq!(
+            check_compatibility(&abi(old_state.clone(), vec![]), &new),
+            vec![BreakingChange::AddedField {
+                path: String::from("state.burned"),
+            }]
+        );
+
+        new.upgrade = Some(UpgradeAbi {
+            name: String::from("migrate"),
+            old_state: old_state.clone(),
+            new_state,
+            error: None,
+        });
+        assert_eq!(check_compatibility(&abi(old_state, vec![]), &new), vec![]);
+    }
+}
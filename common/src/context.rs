@@ -1,7 +1,5 @@
 use std::io::{Read, Write};
 
-use reflection::Reflection;
-
 use crate::address::Address;
 use crate::hash::Hash;
 use crate::serialization::{ReadInt, ReadWrite, WriteInt};
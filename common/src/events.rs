@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+use std::io::{Read, Write};
+
+use reflection::Reflection;
+
+use crate::serialization::{ReadInt, ReadWrite, WriteInt};
+
+/// An event published by a contract, implemented by the `#[event]` attribute.
+///
+/// Events are identified on-chain by `event_id`, which is derived from the name of the type.
+pub trait Event: ReadWrite + Reflection {
+    fn event_id() -> u32;
+}
+
+thread_local! {
+    static PENDING_EVENTS: RefCell<Vec<(u32, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Emit an event from the currently executing action.
+///
+/// The event is appended to the result buffer of the invocation once the action returns.
+pub fn emit<E: Event>(event: &E) {
+    let mut payload = Vec::new();
+    event.write_to(&mut payload).unwrap();
+    PENDING_EVENTS.with(|events| events.borrow_mut().push((E::event_id(), payload)));
+}
+
+/// Remove and return the events emitted since the last call.
+pub fn take_pending_events() -> Vec<(u32, Vec<u8>)> {
+    PENDING_EVENTS.with(|events| events.replace(Vec::new()))
+}
+
+/// Write the events emitted since the last call as the events section of a result buffer.
+///
+/// The section is a big-endian `u32` count followed by every event as its `u32` id and its
+/// length-prefixed payload.
+pub fn write_pending_events<T: Write>(writer: &mut T) -> std::io::Result<()> {
+    let events = take_pending_events();
+    writer.write_u32_be(events.len() as u32)?;
+    for (event_id, payload) in events {
+        writer.write_u32_be(event_id)?;
+        payload.write_to(writer)?;
+    }
+    Ok(())
+}
+
+/// Read an events section written by [`write_pending_events`], as the host does.
+pub fn read_events<T: Read>(reader: &mut T) -> Vec<(u32, Vec<u8>)> {
+    let count = reader.read_u32_be();
+    (0..count)
+        .map(|_| (reader.read_u32_be(), Vec::<u8>::read_from(reader)))
+        .collect()
+}
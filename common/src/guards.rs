@@ -0,0 +1,18 @@
+//! Checks run against the [`ContractContext`] before an action, as declared with
+//! `#[action(only_owner)]` or `#[action(guard = path::to::guard)]`.
+//!
+//! A guard is a function `fn(&ContractContext) -> Result<(), String>`. When a guard fails, the
+//! action is not run and the invocation fails with the error of the guard, converted with
+//! `From<String>` into the error type of actions returning `Result<_, E>`.
+
+use crate::context::ContractContext;
+
+/// Fail unless the action is invoked by the owner of the contract.
+pub fn only_owner(context: &ContractContext) -> Result<(), String> {
+    if context.sender != context.owner {
+        return Err(String::from(
+            "Only the owner of the contract can invoke this action",
+        ));
+    }
+    Ok(())
+}
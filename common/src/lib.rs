@@ -2,8 +2,15 @@ extern crate reflection;
 #[macro_use]
 extern crate reflection_derive;
 
+pub mod abi;
 pub mod address;
 pub mod base;
 pub mod context;
+pub mod events;
+pub mod guards;
 pub mod hash;
+pub mod memory;
+pub mod result;
 pub mod serialization;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
@@ -0,0 +1,75 @@
+//! Ownership of the buffers exchanged between the host and the contract.
+//!
+//! Every buffer lives in the memory of the contract, and is allocated with [`alloc`] and freed
+//! with [`dealloc`]:
+//!
+//! 1. The host allocates each input (context, state and RPC) with `alloc`, writes it, and passes
+//!    its pointer and length to the exported function. The contract only borrows the inputs for
+//!    the duration of the call, and the host frees them with `dealloc` afterwards.
+//! 2. The exported function returns a pointer to a return descriptor: a big-endian `u32` length
+//!    followed by that many bytes of result buffer, as described in [`crate::result`]. The
+//!    descriptor is owned by the host from then on.
+//! 3. Once the result is read, the host frees the descriptor with
+//!    `dealloc(ptr, RETURN_DESCRIPTOR_HEADER_LEN + length)`.
+//!
+//! Pointers and lengths are passed at the native width of the platform, so the protocol is the
+//! same on wasm32 and in native tests.
+
+/// Number of bytes preceding the result buffer in a return descriptor.
+pub const RETURN_DESCRIPTOR_HEADER_LEN: usize = 4;
+
+/// Allocate a zeroed buffer of `len` bytes, to be freed with [`dealloc`].
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    let buffer = vec![0u8; len].into_boxed_slice();
+    Box::into_raw(buffer) as *mut u8
+}
+
+/// Free a buffer of `len` bytes previously returned by [`alloc`] or by an exported function.
+///
+/// # Safety
+///
+/// `ptr` must have been handed to the host by this module with exactly `len` bytes, and must not
+/// be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    let slice = std::ptr::slice_from_raw_parts_mut(ptr, len);
+    drop(Box::from_raw(slice));
+}
+
+/// Hand `result` over to the host as a return descriptor.
+pub fn into_return_descriptor(result: Vec<u8>) -> *mut u8 {
+    let mut descriptor = Vec::with_capacity(RETURN_DESCRIPTOR_HEADER_LEN + result.len());
+    descriptor.extend_from_slice(&(result.len() as u32).to_be_bytes());
+    descriptor.extend_from_slice(&result);
+    Box::into_raw(descriptor.into_boxed_slice()) as *mut u8
+}
+
+/// Copy `bytes` into the host buffer of `dst_len` bytes at `dst_ptr`, and return their length.
+///
+/// Nothing is written if the buffer is too small, which lets the host query the length first.
+///
+/// # Safety
+///
+/// `dst_ptr` must be valid for writes of `dst_len` bytes.
+pub unsafe fn copy_to_buffer(bytes: &[u8], dst_ptr: *mut u8, dst_len: usize) -> usize {
+    if bytes.len() <= dst_len {
+        std::ptr::copy(bytes.as_ptr(), dst_ptr, bytes.len());
+    }
+    bytes.len()
+}
+
+/// Read the result buffer of a return descriptor and free the descriptor.
+///
+/// This is the host side of [`into_return_descriptor`], for use when calling contracts natively.
+///
+/// # Safety
+///
+/// `ptr` must be a return descriptor returned by an exported function, which has not been freed.
+pub unsafe fn take_return_descriptor(ptr: *mut u8) -> Vec<u8> {
+    let header = std::slice::from_raw_parts(ptr, RETURN_DESCRIPTOR_HEADER_LEN);
+    let len = u32::from_be_bytes(header.try_into().unwrap()) as usize;
+    let result = std::slice::from_raw_parts(ptr.add(RETURN_DESCRIPTOR_HEADER_LEN), len).to_vec();
+    dealloc(ptr, RETURN_DESCRIPTOR_HEADER_LEN + len);
+    result
+}
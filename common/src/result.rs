@@ -0,0 +1,110 @@
+//! The buffer returned to the host from an invocation of a contract function.
+//!
+//! The first byte is the status of the invocation. A successful invocation continues with the
+//! length-prefixed new state followed by the events section written by
+//! [`write_pending_events`](crate::events::write_pending_events). A failed invocation continues
+//! with the length-prefixed error, and the host is expected to revert the transaction.
+//!
+//! Views use the successful layout without an events section, with the returned value in place
+//! of the state.
+
+use crate::events::{read_events, take_pending_events, write_pending_events};
+use crate::serialization::{ReadWrite, WriteInt};
+
+pub const STATUS_OK: u8 = 0;
+pub const STATUS_ERROR: u8 = 1;
+
+/// A successful invocation, as read by the host from its result buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    /// The serialized new state.
+    pub state: Vec<u8>,
+    /// The id and serialized payload of every emitted event, in emission order.
+    pub events: Vec<(u32, Vec<u8>)>,
+}
+
+/// Result buffer for an invocation that produced `state`.
+pub fn ok_result<S: ReadWrite>(state: &S) -> Vec<u8> {
+    let mut state_bytes: Vec<u8> = Vec::new();
+    state.write_to(&mut state_bytes).unwrap();
+
+    let mut result: Vec<u8> = Vec::new();
+    result.write_byte(STATUS_OK).unwrap();
+    state_bytes.write_to(&mut result).unwrap();
+    write_pending_events(&mut result).unwrap();
+    result
+}
+
+/// Result buffer for an invocation that failed with `error`.
+///
+/// Events emitted before the failure are discarded along with the rest of the transaction.
+pub fn err_result<E: ReadWrite>(error: &E) -> Vec<u8> {
+    take_pending_events();
+
+    let mut error_bytes: Vec<u8> = Vec::new();
+    error.write_to(&mut error_bytes).unwrap();
+
+    let mut result: Vec<u8> = Vec::new();
+    result.write_byte(STATUS_ERROR).unwrap();
+    error_bytes.write_to(&mut result).unwrap();
+    result
+}
+
+/// Result buffer for an invocation whose RPC does not start with the shortname of an action.
+pub fn unknown_shortname_result(shortname: Option<u32>) -> Vec<u8> {
+    let message = match shortname {
+        Some(shortname) => format!("Unknown action shortname {:#010x}", shortname),
+        None => String::from("Missing action shortname"),
+    };
+    err_result(&message)
+}
+
+/// Result buffer for a call of a view whose RPC does not start with the shortname of a view.
+pub fn unknown_view_shortname_result(shortname: Option<u32>) -> Vec<u8> {
+    let message = match shortname {
+        Some(shortname) => format!("Unknown view shortname {:#010x}", shortname),
+        None => String::from("Missing view shortname"),
+    };
+    err_result(&message)
+}
+
+/// Result buffer for a view that returned `value`.
+///
+/// Views cannot publish anything, so events emitted during the call are discarded.
+pub fn value_result<V: ReadWrite>(value: &V) -> Vec<u8> {
+    take_pending_events();
+
+    let mut value_bytes: Vec<u8> = Vec::new();
+    value.write_to(&mut value_bytes).unwrap();
+
+    let mut result: Vec<u8> = Vec::new();
+    result.write_byte(STATUS_OK).unwrap();
+    value_bytes.write_to(&mut result).unwrap();
+    result
+}
+
+/// Read the result buffer of an action or initializer, as the host does.
+///
+/// A failed invocation is returned as its serialized error.
+pub fn read_result(result: &[u8]) -> Result<Invocation, Vec<u8>> {
+    let mut reader = read_status(result)?;
+    let state = Vec::<u8>::read_from(&mut reader);
+    let events = read_events(&mut reader);
+    Ok(Invocation { state, events })
+}
+
+/// Read the result buffer of a view, as the host does, returning the serialized value.
+pub fn read_value_result(result: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+    let mut reader = read_status(result)?;
+    Ok(Vec::<u8>::read_from(&mut reader))
+}
+
+/// The remainder of a successful result buffer, or the serialized error of a failed one.
+fn read_status(result: &[u8]) -> Result<&[u8], Vec<u8>> {
+    let mut reader = &result[1..];
+    match result[0] {
+        STATUS_OK => Ok(reader),
+        STATUS_ERROR => Err(Vec::<u8>::read_from(&mut reader)),
+        status => panic!("Unrecognized result status {}", status),
+    }
+}
@@ -247,3 +247,108 @@ impl ReadWrite for u64 {
         writer.write_u64_be(*self)
     }
 }
+
+impl ReadWrite for i64 {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        reader.read_i64_be()
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_i64_be(*self)
+    }
+}
+
+impl ReadWrite for u32 {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        reader.read_u32_be()
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_u32_be(*self)
+    }
+}
+
+impl ReadWrite for i32 {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        reader.read_i32_be()
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_i32_be(*self)
+    }
+}
+
+/// Byte arrays are written as is, without a length prefix.
+impl<const N: usize> ReadWrite for [u8; N] {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        let mut result = [0u8; N];
+        reader.read_exact(&mut result).unwrap();
+        result
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_all(self)
+    }
+}
+
+/// Arrays are written element by element, without a length prefix.
+impl<S: ReadWrite, const N: usize> ReadWrite for [S; N] {
+    fn read_from<T: Read>(reader: &mut T) -> Self {
+        std::array::from_fn(|_| S::read_from(reader))
+    }
+
+    fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        for item in self {
+            item.write_to(writer)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! tuple_read_write {
+    ($($name:ident $index:tt),+) => {
+        /// Tuples are written element by element.
+        impl<$($name: ReadWrite),+> ReadWrite for ($($name,)+) {
+            fn read_from<T: Read>(reader: &mut T) -> Self {
+                ($($name::read_from(reader),)+)
+            }
+
+            fn write_to<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+                $(self.$index.write_to(writer)?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+/// The empty tuple is written as nothing, as are the arguments of a function taking none.
+impl ReadWrite for () {
+    fn read_from<T: Read>(_reader: &mut T) -> Self {}
+
+    fn write_to<T: Write>(&self, _writer: &mut T) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+tuple_read_write!(A 0);
+tuple_read_write!(A 0, B 1);
+tuple_read_write!(A 0, B 1, C 2);
+tuple_read_write!(A 0, B 1, C 2, D 3);
+
+/// Read a length-prefixed string by borrowing it from the buffer being read.
+///
+/// The encoding is the same as for `String`, so an argument of type `&str` can be decoded from
+/// the RPC without copying it.
+pub fn read_str<'a>(reader: &mut &'a [u8]) -> &'a str {
+    std::str::from_utf8(read_bytes(reader)).unwrap()
+}
+
+/// Read length-prefixed bytes by borrowing them from the buffer being read.
+///
+/// The encoding is the same as for `Vec<u8>`.
+pub fn read_bytes<'a>(reader: &mut &'a [u8]) -> &'a [u8] {
+    let len = reader.read_u32_be() as usize;
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    bytes
+}
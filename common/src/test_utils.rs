@@ -0,0 +1,273 @@
+//! Native testing of contracts, without compiling them to wasm.
+//!
+//! A [`TestContract`] invokes the entry points generated by `#[contract]` the way the host does,
+//! following the protocol of [`crate::memory`], and keeps the serialized state between
+//! invocations:
+//!
+//! ```ignore
+//! let mut token = TestContract::<TokenContractState>::deploy(
+//!     EntryPoints { init, execute, view, abi: export_contract_schema_binary },
+//!     owner,
+//!     (None::<TokenContractState>,),
+//! ).unwrap();
+//!
+//! token.action("mint", (100u64,)).unwrap();
+//! token.set_sender(alice);
+//! assert!(token.action("transfer", (bob, 500u64)).is_err());
+//! assert_eq!(token.state().total_supply, 100);
+//! assert_eq!(token.view::<_, u64>("get_balance", (owner,)), 100);
+//! ```
+//!
+//! The host functions called by the contract are served by [`host`], the mock host of
+//! `pbc_external`. The RPC of each invocation is loaded as the payload of the host before the
+//! contract is invoked, so the contract reads it with `read_context_into_address`.
+
+use std::marker::PhantomData;
+
+use crate::abi::{ContractAbi, FunctionKind};
+use crate::address::Address;
+use crate::context::ContractContext;
+use crate::events::Event;
+use crate::hash::Hash;
+use crate::memory::take_return_descriptor;
+use crate::result::{read_result, read_value_result};
+use crate::serialization::ReadWrite;
+
+pub use pbc_external::mock as host;
+
+/// The `init` export, taking the context and the RPC.
+pub type InitEntryPoint = extern "C" fn(*const u8, usize, *const u8, usize) -> *mut u8;
+
+/// The `execute` export, taking the context, the state and the RPC. The `view` export shares its
+/// signature.
+pub type ExecuteEntryPoint =
+    extern "C" fn(*const u8, usize, *const u8, usize, *const u8, usize) -> *mut u8;
+
+/// The `export_contract_schema_binary` export, writing the ABI into a buffer.
+pub type AbiEntryPoint = extern "C" fn(*mut u8, usize) -> usize;
+
+/// The exports of a contract crate, which are public functions at the root of the crate.
+#[derive(Clone, Copy)]
+pub struct EntryPoints {
+    pub init: InitEntryPoint,
+    pub execute: ExecuteEntryPoint,
+    pub view: ExecuteEntryPoint,
+    pub abi: AbiEntryPoint,
+}
+
+/// The address of every contract deployed with [`TestContract::deploy`].
+pub const TEST_CONTRACT_ADDRESS: Address = Address::PublicContract([0xcc; 20]);
+
+/// A contract deployed in a test, with state `S` and errors of type `E`.
+///
+/// Every invocation gets a fresh context: the owner is the deployer, the sender is the owner
+/// until changed with [`set_sender`](Self::set_sender), and each invocation is its own original
+/// transaction with a distinct hash. Block time starts at 1 and only moves when advanced.
+///
+/// Failed invocations leave the state untouched, as the host reverts the transaction. Errors
+/// are read as `E`, which defaults to `String`, the error of the dispatcher and of most
+/// contracts. A contract with its own error type is deployed as `TestContract<S, MyError>`, and
+/// [`invoke_raw`](Self::invoke_raw) returns the serialized error of any other invocation.
+/// A panic in the contract cannot unwind through the entry point, and aborts the test.
+pub struct TestContract<S, E = String> {
+    entry_points: EntryPoints,
+    abi: ContractAbi,
+    owner: Address,
+    sender: Address,
+    block_time: i64,
+    transactions: u64,
+    state: Vec<u8>,
+    events: Vec<(u32, Vec<u8>)>,
+    phantom: PhantomData<(S, E)>,
+}
+
+impl<S: ReadWrite, E: ReadWrite> TestContract<S, E> {
+    /// Deploy the contract as `owner`, with `arguments` as the arguments of its initializer.
+    pub fn deploy<A: ReadWrite>(
+        entry_points: EntryPoints,
+        owner: Address,
+        arguments: A,
+    ) -> Result<Self, E> {
+        let mut contract = TestContract {
+            entry_points,
+            abi: read_abi(entry_points.abi),
+            owner,
+            sender: owner,
+            block_time: 1,
+            transactions: 0,
+            state: Vec::new(),
+            events: Vec::new(),
+            phantom: PhantomData,
+        };
+
+        let ctx = contract.next_context();
+        let rpc = encode(&arguments);
+        host::load_payload(rpc.clone());
+        let descriptor = (entry_points.init)(ctx.as_ptr(), ctx.len(), rpc.as_ptr(), rpc.len());
+        contract.apply(descriptor).map_err(|error| decode(&error))?;
+        Ok(contract)
+    }
+
+    /// Invoke the action `name` with `arguments`, given as a tuple in declaration order.
+    ///
+    /// Panics if the contract has no such action.
+    pub fn action<A: ReadWrite>(&mut self, name: &str, arguments: A) -> Result<(), E> {
+        let shortname = self.shortname(FunctionKind::Action, name);
+        let mut rpc = shortname.to_be_bytes().to_vec();
+        arguments.write_to(&mut rpc).unwrap();
+        self.invoke(&rpc)
+    }
+
+    /// Invoke the contract with `rpc`, the shortname of an action followed by its arguments.
+    pub fn invoke(&mut self, rpc: &[u8]) -> Result<(), E> {
+        self.invoke_raw(rpc).map_err(|error| decode(&error))
+    }
+
+    /// Invoke the contract with `rpc` like [`invoke`](Self::invoke), returning the error in its
+    /// serialized form.
+    pub fn invoke_raw(&mut self, rpc: &[u8]) -> Result<(), Vec<u8>> {
+        let ctx = self.next_context();
+        host::load_payload(rpc.to_vec());
+        let descriptor = (self.entry_points.execute)(
+            ctx.as_ptr(),
+            ctx.len(),
+            self.state.as_ptr(),
+            self.state.len(),
+            rpc.as_ptr(),
+            rpc.len(),
+        );
+        self.apply(descriptor)
+    }
+
+    /// Call the view `name` with `arguments`, given as a tuple in declaration order, and decode
+    /// the value it returns.
+    ///
+    /// Views never change the state, which is kept as it is. Panics if the contract has no such
+    /// view.
+    pub fn view<A: ReadWrite, V: ReadWrite>(&mut self, name: &str, arguments: A) -> V {
+        let shortname = self.shortname(FunctionKind::View, name);
+        let mut rpc = shortname.to_be_bytes().to_vec();
+        arguments.write_to(&mut rpc).unwrap();
+
+        let ctx = self.next_context();
+        host::load_payload(rpc.clone());
+        let descriptor = (self.entry_points.view)(
+            ctx.as_ptr(),
+            ctx.len(),
+            self.state.as_ptr(),
+            self.state.len(),
+            rpc.as_ptr(),
+            rpc.len(),
+        );
+        let result = unsafe { take_return_descriptor(descriptor) };
+        let value = read_value_result(&result).expect("Views cannot fail");
+        V::read_from(&mut value.as_slice())
+    }
+
+    /// The current state of the contract.
+    pub fn state(&self) -> S {
+        S::read_from(&mut self.state.as_slice())
+    }
+
+    /// The current state of the contract in its serialized form.
+    pub fn state_bytes(&self) -> &[u8] {
+        &self.state
+    }
+
+    /// The events of type `T` emitted by the last successful invocation, in emission order.
+    pub fn events<T: Event>(&self) -> Vec<T> {
+        self.events
+            .iter()
+            .filter(|(event_id, _)| *event_id == T::event_id())
+            .map(|(_, payload)| T::read_from(&mut payload.as_slice()))
+            .collect()
+    }
+
+    /// The ABI exported by the contract.
+    pub fn abi(&self) -> &ContractAbi {
+        &self.abi
+    }
+
+    pub fn owner(&self) -> Address {
+        self.owner
+    }
+
+    pub fn sender(&self) -> Address {
+        self.sender
+    }
+
+    /// Send the following invocations from `sender`.
+    pub fn set_sender(&mut self, sender: Address) {
+        self.sender = sender;
+    }
+
+    pub fn block_time(&self) -> i64 {
+        self.block_time
+    }
+
+    /// Move the block time of the following invocations `blocks` ahead.
+    pub fn advance_block_time(&mut self, blocks: i64) {
+        self.block_time += blocks;
+    }
+
+    /// The shortname of the function `name` of kind `kind`, which the contract must have.
+    fn shortname(&self, kind: FunctionKind, name: &str) -> u32 {
+        self.abi
+            .functions
+            .iter()
+            .find(|function| function.kind == kind && function.name == name)
+            .and_then(|function| function.shortname)
+            .unwrap_or_else(|| {
+                let kind = format!("{:?}", kind).to_lowercase();
+                panic!("The contract has no {} `{}`", kind, name)
+            })
+    }
+
+    /// The serialized context of the next invocation.
+    fn next_context(&mut self) -> Vec<u8> {
+        self.transactions += 1;
+        let mut transaction: Hash = [0u8; 32];
+        transaction[24..].copy_from_slice(&self.transactions.to_be_bytes());
+
+        let context = ContractContext {
+            owner: self.owner,
+            contract_address: TEST_CONTRACT_ADDRESS,
+            sender: self.sender,
+            block_time: self.block_time,
+            block_production_time: self.block_time,
+            current_transaction: transaction,
+            original_transaction: transaction,
+        };
+        encode(&context)
+    }
+
+    /// Keep the state and the events of a successful invocation, or return its serialized error.
+    fn apply(&mut self, descriptor: *mut u8) -> Result<(), Vec<u8>> {
+        let result = unsafe { take_return_descriptor(descriptor) };
+        match read_result(&result) {
+            Ok(invocation) => {
+                self.state = invocation.state;
+                self.events = invocation.events;
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+fn read_abi(export: AbiEntryPoint) -> ContractAbi {
+    let len = export(std::ptr::null_mut(), 0);
+    let mut buffer = vec![0u8; len];
+    export(buffer.as_mut_ptr(), buffer.len());
+    ContractAbi::from_binary(&buffer)
+}
+
+fn decode<T: ReadWrite>(bytes: &[u8]) -> T {
+    T::read_from(&mut &bytes[..])
+}
+
+fn encode<T: ReadWrite>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.write_to(&mut bytes).unwrap();
+    bytes
+}
@@ -0,0 +1,25 @@
+//! The single dependency of a contract.
+//!
+//! Re-exports the contract attribute macros together with the modules of
+//! `pbc-contract-common` and the `reflection` crate. The code generated by the macros refers to
+//! these re-exports by their full path, so a contract does not need any other dependency or
+//! import for the generated code to compile.
+//!
+//! Contracts import the macros and the types they use from [`prelude`].
+
+pub use pbc_contract_codegen::{action, contract, event, init, state, upgrade, view};
+pub use pbc_contract_common::*;
+pub use reflection;
+pub use reflection::Reflection;
+pub use reflection_derive::Reflection;
+
+/// The items needed to write a contract, imported with `use pbc_contract::prelude::*;`.
+pub mod prelude {
+    pub use pbc_contract_codegen::{action, contract, event, init, state, upgrade, view};
+    pub use pbc_contract_common::address::Address;
+    pub use pbc_contract_common::context::ContractContext;
+    pub use pbc_contract_common::events::emit;
+    pub use pbc_contract_common::serialization::ReadWrite;
+    pub use reflection::Reflection;
+    pub use reflection_derive::Reflection;
+}
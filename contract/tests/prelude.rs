@@ -0,0 +1,64 @@
+use pbc_contract::abi::SchemaAbi;
+use pbc_contract::prelude::*;
+
+#[contract]
+mod counter {
+    use super::*;
+
+    #[state]
+    pub struct CounterState {
+        pub owner: Address,
+        pub count: u64,
+    }
+
+    #[event]
+    pub struct Incremented {
+        by: u64,
+    }
+
+    #[contract]
+    impl CounterState {
+        #[init]
+        pub fn initialize(context: ContractContext) -> Self {
+            CounterState {
+                owner: context.owner,
+                count: 0,
+            }
+        }
+
+        #[action(only_owner)]
+        pub fn increment(&mut self, _context: ContractContext, by: u64) {
+            self.count += by;
+            emit(&Incremented { by });
+        }
+
+        #[view]
+        pub fn count(&self, _context: ContractContext) -> u64 {
+            self.count
+        }
+    }
+}
+
+use counter::*;
+
+#[test]
+fn a_contract_compiles_with_only_the_prelude() {
+    let abi = counter::__contract_abi();
+
+    assert_eq!(abi.state, SchemaAbi::of::<CounterState>());
+    let names: Vec<&str> = abi.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["initialize", "increment", "count"]);
+    assert_eq!(abi.events[0].name, "Incremented");
+}
+
+#[test]
+fn prelude_read_write_round_trips_the_state() {
+    let state = CounterState {
+        owner: Address::Account([1u8; 20]),
+        count: 3,
+    };
+    let mut bytes = Vec::new();
+    state.write_to(&mut bytes).unwrap();
+
+    assert_eq!(CounterState::read_from(&mut bytes.as_slice()), state);
+}
@@ -0,0 +1,114 @@
+use pbc_contract::prelude::*;
+use pbc_contract_common::test_utils::{EntryPoints, TestContract};
+
+#[contract]
+mod notary {
+    use super::*;
+
+    #[state]
+    pub struct NotaryState {
+        pub note: u64,
+        pub payload: Vec<u8>,
+        pub stamps: u32,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Reflection)]
+    #[reflection(crate = "::pbc_contract::reflection")]
+    pub struct StampLimit {
+        pub limit: u32,
+    }
+
+    impl ReadWrite for StampLimit {
+        fn read_from<T: std::io::Read>(reader: &mut T) -> Self {
+            StampLimit {
+                limit: u32::read_from(reader),
+            }
+        }
+
+        fn write_to<T: std::io::Write>(&self, writer: &mut T) -> std::io::Result<()> {
+            self.limit.write_to(writer)
+        }
+    }
+
+    #[contract]
+    impl NotaryState {
+        #[init]
+        pub fn initialize(_context: ContractContext) -> Self {
+            NotaryState {
+                note: 0,
+                payload: Vec::new(),
+                stamps: 0,
+            }
+        }
+
+        /// Keep the payload served by the host, which is the RPC of the invocation.
+        #[action(shortname = 0x01)]
+        pub fn record(&mut self, _context: ContractContext, note: u64) {
+            let len = unsafe { pbc_external::read_context_into_address(0, 0) };
+            let mut payload = vec![0u8; len as usize];
+            unsafe {
+                pbc_external::read_context_into_address(payload.as_mut_ptr() as i64, len);
+            }
+            self.note = note;
+            self.payload = payload;
+        }
+
+        #[action(shortname = 0x02)]
+        pub fn stamp(mut self, _context: ContractContext) -> Result<Self, StampLimit> {
+            if self.stamps == 2 {
+                return Err(StampLimit { limit: 2 });
+            }
+            self.stamps += 1;
+            Ok(self)
+        }
+    }
+}
+
+use notary::*;
+
+const OWNER: Address = Address::Account([1u8; 20]);
+
+fn deploy() -> TestContract<NotaryState, StampLimit> {
+    let entry_points = EntryPoints {
+        init: notary::init,
+        execute: notary::execute,
+        view: notary::view,
+        abi: notary::export_contract_schema_binary,
+    };
+    TestContract::deploy(entry_points, OWNER, ()).unwrap()
+}
+
+#[test]
+fn actions_read_the_rpc_from_the_host() {
+    let mut notary = deploy();
+
+    notary.action("record", (7u64,)).unwrap();
+
+    assert_eq!(notary.state().note, 7);
+    assert_eq!(
+        notary.state().payload,
+        vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 7]
+    );
+}
+
+#[test]
+fn errors_are_decoded_as_the_error_type_of_the_contract() {
+    let mut notary = deploy();
+    notary.action("stamp", ()).unwrap();
+    notary.action("stamp", ()).unwrap();
+
+    let error = notary.action("stamp", ()).unwrap_err();
+
+    assert_eq!(error, StampLimit { limit: 2 });
+    assert_eq!(notary.state().stamps, 2);
+}
+
+#[test]
+fn raw_invocations_return_the_serialized_error() {
+    let mut notary = deploy();
+
+    let error = notary.invoke_raw(&[0, 0, 0, 9]).unwrap_err();
+
+    let message = String::read_from(&mut error.as_slice());
+    assert!(message.contains("9"), "{}", message);
+}
@@ -6,15 +6,4 @@ extern "C" {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-mod dummy {
-    #[no_mangle]
-    pub extern "C" fn read_context_into_address(_addr: i64, _len: i32) -> i32 {
-        0
-    }
-
-    #[no_mangle]
-    pub extern "C" fn call_named(_name_ptr: i64, _name_len: i32, _rpc_ptr: i64, _rpc_len: i32) {}
-
-    #[no_mangle]
-    pub extern "C" fn log_external(_message_ptr: i64, _message_len: i32) {}
-}
+pub mod mock;
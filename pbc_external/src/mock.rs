@@ -0,0 +1,226 @@
+//! A host for running contracts natively, in place of the `ext` imports of the wasm host.
+//!
+//! The host is thread-local, so every test has its own. Tests load the payload read by
+//! `read_context_into_address`, and inspect the messages logged and the calls made by the
+//! contract. Calls to a name registered with [`register`] are routed to its native entry point,
+//! which lets a test link several contracts together.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A call made by the contract with `call_named`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub name: String,
+    pub rpc: Vec<u8>,
+}
+
+/// The native entry point invoked with the RPC of a call routed to it.
+pub type EntryPoint = Rc<dyn Fn(&[u8])>;
+
+#[derive(Default)]
+struct Host {
+    payload: Vec<u8>,
+    logs: Vec<String>,
+    calls: Vec<Call>,
+    entry_points: HashMap<String, EntryPoint>,
+}
+
+thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::default());
+}
+
+/// Load the payload returned by the following calls to `read_context_into_address`.
+pub fn load_payload(payload: Vec<u8>) {
+    HOST.with(|host| host.borrow_mut().payload = payload);
+}
+
+/// Route the calls to `name` to `entry_point`, replacing any entry point registered before.
+pub fn register<F: Fn(&[u8]) + 'static>(name: &str, entry_point: F) {
+    HOST.with(|host| {
+        host.borrow_mut()
+            .entry_points
+            .insert(name.to_string(), Rc::new(entry_point))
+    });
+}
+
+/// Remove and return the messages logged since the last call.
+pub fn take_logs() -> Vec<String> {
+    HOST.with(|host| std::mem::take(&mut host.borrow_mut().logs))
+}
+
+/// Remove and return the calls made since the last call, including the routed ones.
+pub fn take_calls() -> Vec<Call> {
+    HOST.with(|host| std::mem::take(&mut host.borrow_mut().calls))
+}
+
+/// Clear the payload, the recorded logs and calls, and the registered entry points.
+pub fn reset() {
+    HOST.with(|host| *host.borrow_mut() = Host::default());
+}
+
+/// Copy the loaded payload to the `len` bytes at `addr`, and return the length of the payload.
+///
+/// Nothing is written if the buffer is too small, which lets the contract query the length
+/// first, or if `len` is negative.
+#[no_mangle]
+pub extern "C" fn read_context_into_address(addr: i64, len: i32) -> i32 {
+    HOST.with(|host| {
+        let payload = &host.borrow().payload;
+        let fits = usize::try_from(len).is_ok_and(|len| payload.len() <= len);
+        if fits {
+            unsafe { std::ptr::copy(payload.as_ptr(), addr as *mut u8, payload.len()) };
+        }
+        payload.len() as i32
+    })
+}
+
+/// Record the call, and invoke the entry point registered for its name if any.
+#[no_mangle]
+pub extern "C" fn call_named(name_ptr: i64, name_len: i32, rpc_ptr: i64, rpc_len: i32) {
+    let name = String::from_utf8(unsafe { read_bytes(name_ptr, name_len) }).unwrap();
+    let rpc = unsafe { read_bytes(rpc_ptr, rpc_len) };
+
+    // The host is released before the call, as the entry point may use it in turn.
+    let entry_point = HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        host.calls.push(Call {
+            name: name.clone(),
+            rpc: rpc.clone(),
+        });
+        host.entry_points.get(&name).cloned()
+    });
+    if let Some(entry_point) = entry_point {
+        entry_point(&rpc);
+    }
+}
+
+/// Record the message.
+#[no_mangle]
+pub extern "C" fn log_external(message_ptr: i64, message_len: i32) {
+    let message = unsafe { read_bytes(message_ptr, message_len) };
+    let message = String::from_utf8_lossy(&message).into_owned();
+    HOST.with(|host| host.borrow_mut().logs.push(message));
+}
+
+unsafe fn read_bytes(ptr: i64, len: i32) -> Vec<u8> {
+    if len == 0 {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(ptr as *const u8, len as usize).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn call(name: &str, rpc: &[u8]) {
+        unsafe {
+            crate::call_named(
+                name.as_ptr() as i64,
+                name.len() as i32,
+                rpc.as_ptr() as i64,
+                rpc.len() as i32,
+            )
+        }
+    }
+
+    #[test]
+    fn reads_the_loaded_payload() {
+        load_payload(vec![1, 2, 3]);
+        let mut buffer = [0u8; 4];
+
+        let len = unsafe { crate::read_context_into_address(buffer.as_mut_ptr() as i64, 4) };
+
+        assert_eq!(len, 3);
+        assert_eq!(buffer, [1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn reports_the_payload_length_without_writing_to_a_small_buffer() {
+        load_payload(vec![1, 2, 3]);
+        let mut buffer = [0u8; 2];
+
+        let len = unsafe { crate::read_context_into_address(buffer.as_mut_ptr() as i64, 2) };
+
+        assert_eq!(len, 3);
+        assert_eq!(buffer, [0, 0]);
+    }
+
+    #[test]
+    fn writes_nothing_for_a_negative_length() {
+        load_payload(vec![1, 2, 3]);
+        let mut buffer = [0u8; 4];
+
+        let len = unsafe { crate::read_context_into_address(buffer.as_mut_ptr() as i64, -1) };
+
+        assert_eq!(len, 3);
+        assert_eq!(buffer, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn records_logs() {
+        let message = "Minted 10";
+        unsafe { crate::log_external(message.as_ptr() as i64, message.len() as i32) };
+
+        assert_eq!(take_logs(), vec![String::from("Minted 10")]);
+        assert!(take_logs().is_empty());
+    }
+
+    #[test]
+    fn routes_calls_to_registered_entry_points() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        register("deposit", move |rpc| sink.borrow_mut().push(rpc.to_vec()));
+
+        call("deposit", &[7, 8]);
+        call("withdraw", &[9]);
+
+        assert_eq!(*received.borrow(), vec![vec![7, 8]]);
+        assert_eq!(
+            take_calls(),
+            vec![
+                Call {
+                    name: String::from("deposit"),
+                    rpc: vec![7, 8],
+                },
+                Call {
+                    name: String::from("withdraw"),
+                    rpc: vec![9],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn entry_points_may_call_back_into_the_host() {
+        let depth = Rc::new(Cell::new(0));
+        let counter = depth.clone();
+        register("ping", move |_| {
+            counter.set(counter.get() + 1);
+            if counter.get() < 3 {
+                call("ping", &[]);
+            }
+        });
+
+        call("ping", &[]);
+
+        assert_eq!(depth.get(), 3);
+        assert_eq!(take_calls().len(), 3);
+    }
+
+    #[test]
+    fn reset_forgets_entry_points() {
+        let called = Rc::new(Cell::new(false));
+        let flag = called.clone();
+        register("ping", move |_| flag.set(true));
+
+        reset();
+        call("ping", &[]);
+
+        assert!(!called.get());
+    }
+}
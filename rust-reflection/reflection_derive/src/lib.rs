@@ -14,48 +14,68 @@ use syn::Meta::NameValue;
 use syn::NestedMeta::Meta;
 use syn::{Data, DeriveInput, Fields, GenericParam, Generics, Type};
 
-#[proc_macro_derive(Reflection)]
+#[proc_macro_derive(Reflection, attributes(reflection))]
 pub fn derive_reflection(input: TokenStream) -> TokenStream {
     let input: DeriveInput = syn::parse(input).unwrap();
+    let krate = crate_path(&input.attrs);
     let name = input.ident;
-    let generics = add_trait_bounds(input.generics);
+    let generics = add_trait_bounds(input.generics, &krate);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    let members = aggregate(&input.data);
-    let schema = generate_schema(&input.data);
-    let ty = generate_ty(&input.data);
+    let members = aggregate(&input.data, &krate);
+    let schema = generate_schema(&input.data, &krate);
+    let ty = generate_ty(&input.data, &krate);
 
     let expanded = quote! {
-        impl #impl_generics ::reflection::Reflection for #name #ty_generics #where_clause {
-            fn ty() -> ::reflection::Type { #ty }
-            fn name() -> ::reflection::Name { Some( String::from( stringify!( #name )))}
+        impl #impl_generics #krate::Reflection for #name #ty_generics #where_clause {
+            fn ty() -> #krate::Type { #ty }
+            fn name() -> #krate::Name { Some( String::from( stringify!( #name )))}
 
-            fn schema( id: ::reflection::Id ) -> ::reflection::Schema {
+            fn schema( id: #krate::Id ) -> #krate::Schema {
                 let mut tree = {#schema};
                 match &mut tree.root_mut().data_mut() {
-                    ::reflection::Member::Field( ref mut field ) => {
+                    #krate::Member::Field( ref mut field ) => {
                         field.id = id;
                         field.tyname = Self::name();
-                        field.expander = Some( <#name #ty_generics as Reflection>::members );
+                        field.expander = Some( <#name #ty_generics as #krate::Reflection>::members );
                     },
-                    ::reflection::Member::Variant( ref mut variant ) => {
+                    #krate::Member::Variant( ref mut variant ) => {
                         variant.id = id;
                     },
                 }
                 tree
             }
 
-            fn members() -> ::reflection::Schemas { #members }
+            fn members() -> #krate::Schemas { #members }
         }
     };
 
     expanded.into()
 }
 
-fn add_trait_bounds(mut generics: Generics) -> Generics {
+/// The path of the `reflection` crate in the generated code, given as
+/// `#[reflection(crate = "path")]` by crates re-exporting it, and `::reflection` otherwise.
+fn crate_path(attrs: &[syn::Attribute]) -> syn::Path {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("reflection")) {
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            for nested in list.nested {
+                match nested {
+                    Meta(NameValue(ref value)) if value.path.is_ident("crate") => {
+                        if let syn::Lit::Str(ref lit) = value.lit {
+                            return lit.parse().unwrap();
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+        }
+    }
+    syn::parse_quote!(::reflection)
+}
+
+fn add_trait_bounds(mut generics: Generics, krate: &syn::Path) -> Generics {
     for param in &mut generics.params {
         if let GenericParam::Type(ref mut type_param) = *param {
-            let bound = syn::parse_str("::reflection::Reflection").unwrap();
-            type_param.bounds.push(bound);
+            type_param.bounds.push(syn::parse_quote!(#krate::Reflection));
         }
     }
     generics
@@ -108,32 +128,32 @@ fn serde_rename(field: &syn::Field) -> Option<String> {
     None
 }
 
-fn generate_ty(data: &Data) -> proc_macro2::TokenStream {
+fn generate_ty(data: &Data, krate: &syn::Path) -> proc_macro2::TokenStream {
     match *data {
         Data::Struct(_) => {
-            quote!(::reflection::Type::Struct)
+            quote!(#krate::Type::Struct)
         }
         Data::Enum(_) => {
-            quote!(::reflection::Type::Enum)
+            quote!(#krate::Type::Enum)
         }
         Data::Union(_) => unimplemented!(),
     }
 }
 
-fn generate_schema(data: &Data) -> proc_macro2::TokenStream {
+fn generate_schema(data: &Data, krate: &syn::Path) -> proc_macro2::TokenStream {
     match *data {
         Data::Struct(_) => {
-            quote!(::reflection::field(
+            quote!(#krate::field(
                 "_",
-                ::reflection::Type::Struct,
+                #krate::Type::Struct,
                 None,
                 None
             ))
         }
         Data::Enum(_) => {
-            quote!(::reflection::field(
+            quote!(#krate::field(
                 "_",
-                ::reflection::Type::Enum,
+                #krate::Type::Enum,
                 None,
                 None
             ))
@@ -142,7 +162,7 @@ fn generate_schema(data: &Data) -> proc_macro2::TokenStream {
     }
 }
 
-fn aggregate(data: &Data) -> proc_macro2::TokenStream {
+fn aggregate(data: &Data, krate: &syn::Path) -> proc_macro2::TokenStream {
     match *data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
@@ -161,11 +181,11 @@ fn aggregate(data: &Data) -> proc_macro2::TokenStream {
                 let ftypes3 = fields.named.iter().map(|f| f.ty.clone());
                 quote! {
                     #(
-                        -( ::reflection::field(
+                        -( #krate::field(
                                 #fnames,
-                                <#ftypes1 as ::reflection::Reflection>::ty(),
-                                <#ftypes2 as ::reflection::Reflection>::name(),
-                                Some( <#ftypes3 as ::reflection::Reflection>::members )))
+                                <#ftypes1 as #krate::Reflection>::ty(),
+                                <#ftypes2 as #krate::Reflection>::name(),
+                                Some( <#ftypes3 as #krate::Reflection>::members )))
                     )*
                 }
             }
@@ -187,16 +207,16 @@ fn aggregate(data: &Data) -> proc_macro2::TokenStream {
                 let ftypes3 = fields.unnamed.iter().map(|f| f.ty.clone());
                 quote! {
                     #(
-                        -( ::reflection::field(
+                        -( #krate::field(
                                 #indices,
-                                <#ftypes1 as ::reflection::Reflection>::ty(),
-                                <#ftypes2 as ::reflection::Reflection>::name(),
-                                Some( <#ftypes3 as ::reflection::Reflection>::members )))
+                                <#ftypes1 as #krate::Reflection>::ty(),
+                                <#ftypes2 as #krate::Reflection>::name(),
+                                Some( <#ftypes3 as #krate::Reflection>::members )))
                     )*
                 }
             }
             Fields::Unit => {
-                quote!(::reflection::Schemas::new())
+                quote!(#krate::Schemas::new())
             }
         },
         Data::Enum(ref data) => {
@@ -236,14 +256,14 @@ fn aggregate(data: &Data) -> proc_macro2::TokenStream {
 
             quote! {
                 #(
-                    -( ::reflection::variant( stringify!( #vnames ))
+                    -( #krate::variant( stringify!( #vnames ))
                         /(
                             #(
-                                -( ::reflection::field(
+                                -( #krate::field(
                                         #fnames,
-                                        <#ftypes1 as ::reflection::Reflection>::ty(),
-                                        <#ftypes2 as ::reflection::Reflection>::name(),
-                                        Some( <#ftypes3 as ::reflection::Reflection>::members )))
+                                        <#ftypes1 as #krate::Reflection>::ty(),
+                                        <#ftypes2 as #krate::Reflection>::name(),
+                                        Some( <#ftypes3 as #krate::Reflection>::members )))
                             )*
                         )
                     )
@@ -0,0 +1,281 @@
+//! Execution of an export of a contract module, with the `ext` imports served by the host.
+//!
+//! Every execution runs in a fresh instance of the module. The inputs are copied into the
+//! memory of the instance with its `alloc` export, and the result buffer is read back from the
+//! return descriptor, following the protocol of `pbc_contract_common::memory`.
+//!
+//! Executions are metered with the fuel of the interpreter, which is consumed by every executed
+//! instruction, and by bulk memory operations in proportion to the bytes they touch. An
+//! execution consuming all the fuel it is given traps.
+
+use std::ops::Range;
+
+use pbc_contract_common::memory::RETURN_DESCRIPTOR_HEADER_LEN;
+use wasmi::core::{Trap, TrapCode};
+use wasmi::{Caller, Engine, Error, Extern, Func, Linker, Memory, Module, Store, TypedFunc, Value};
+
+use crate::Call;
+
+/// The state of the host during an execution.
+#[derive(Default)]
+struct Host {
+    context: Vec<u8>,
+    logs: Vec<String>,
+    calls: Vec<Call>,
+}
+
+/// An execution of an export, with what the contract did through the host until it returned
+/// or trapped.
+pub(crate) struct Execution {
+    /// The result buffer, or the trap that aborted the execution.
+    pub result: Result<Vec<u8>, String>,
+    pub logs: Vec<String>,
+    pub calls: Vec<Call>,
+    /// The fuel consumed by the instance, including the copying of the inputs.
+    pub fuel: u64,
+}
+
+/// An engine metering the fuel of executions.
+pub(crate) fn engine() -> Engine {
+    let mut config = wasmi::Config::default();
+    config.consume_fuel(true);
+    Engine::new(&config)
+}
+
+/// Call `export` with a pointer and length for each of `inputs`, the first of which is the
+/// serialized context, and read the result buffer it hands back, with at most `fuel` to
+/// consume.
+pub(crate) fn execute(
+    engine: &Engine,
+    module: &Module,
+    export: &str,
+    inputs: &[&[u8]],
+    fuel: u64,
+) -> Execution {
+    let host = Host {
+        context: inputs[0].to_vec(),
+        ..Host::default()
+    };
+    let mut store = store(engine, host, fuel);
+    let result = Instance::new(&mut store, module)
+        .and_then(|instance| instance.call(&mut store, export, inputs));
+
+    let fuel = store.fuel_consumed().unwrap();
+    let host = store.into_data();
+    Execution {
+        result,
+        logs: host.logs,
+        calls: host.calls,
+        fuel,
+    }
+}
+
+/// The ABI written by the `export_contract_schema_binary` export of the module, with at most
+/// `fuel` to consume.
+pub(crate) fn read_abi(engine: &Engine, module: &Module, fuel: u64) -> Result<Vec<u8>, String> {
+    let mut store = store(engine, Host::default(), fuel);
+    let instance = Instance::new(&mut store, module)?;
+    let export = instance
+        .wasm
+        .get_typed_func::<(i32, i32), i32>(&store, "export_contract_schema_binary")
+        .map_err(|_| missing_export("export_contract_schema_binary"))?;
+
+    let len = export
+        .call(&mut store, (0, 0))
+        .map_err(|error| trapped("export_contract_schema_binary", error))?;
+    // The buffer is checked against the memory before it is allocated.
+    checked_range(instance.memory.data(&store).len(), 0, len.into()).map_err(|error| {
+        format!(
+            "`export_contract_schema_binary` needs an invalid buffer: {}",
+            error
+        )
+    })?;
+    let ptr = instance.write(&mut store, &vec![0u8; len as usize])?;
+    export
+        .call(&mut store, (ptr, len))
+        .map_err(|error| trapped("export_contract_schema_binary", error))?;
+    instance.read(&store, address(ptr), len.into())
+}
+
+/// An instance of a contract module.
+struct Instance {
+    wasm: wasmi::Instance,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+}
+
+impl Instance {
+    fn new(store: &mut Store<Host>, module: &Module) -> Result<Self, String> {
+        let wasm = linker(store.engine())
+            .instantiate(&mut *store, module)
+            .and_then(|instance| instance.start(&mut *store))
+            .map_err(|error| format!("Could not instantiate the contract: {}", error))?;
+        let memory = wasm
+            .get_memory(&*store, "memory")
+            .ok_or("The contract does not export its memory")?;
+        let alloc = wasm
+            .get_typed_func::<i32, i32>(&*store, "alloc")
+            .map_err(|_| missing_export("alloc"))?;
+        Ok(Instance {
+            wasm,
+            memory,
+            alloc,
+        })
+    }
+
+    /// Call `export` with `inputs`, and return the result buffer of its return descriptor.
+    fn call(
+        &self,
+        store: &mut Store<Host>,
+        export: &str,
+        inputs: &[&[u8]],
+    ) -> Result<Vec<u8>, String> {
+        let function: Func = self
+            .wasm
+            .get_func(&*store, export)
+            .ok_or_else(|| missing_export(export))?;
+
+        let mut arguments = Vec::new();
+        for input in inputs {
+            arguments.push(Value::I32(self.write(store, input)?));
+            arguments.push(Value::I32(input.len() as i32));
+        }
+        let mut descriptor = [Value::I32(0)];
+        function
+            .call(&mut *store, &arguments, &mut descriptor)
+            .map_err(|error| trapped(export, error))?;
+        let ptr = match descriptor[0] {
+            Value::I32(ptr) => address(ptr),
+            _ => return Err(format!("`{}` did not return a return descriptor", export)),
+        };
+
+        let header_len = RETURN_DESCRIPTOR_HEADER_LEN as i64;
+        let header = self.read(store, ptr, header_len)?;
+        let len = u32::from_be_bytes(header.try_into().unwrap());
+        self.read(store, ptr + header_len, len.into())
+    }
+
+    /// Copy `bytes` into a buffer allocated by the contract, and return its address.
+    fn write(&self, store: &mut Store<Host>, bytes: &[u8]) -> Result<i32, String> {
+        let ptr = self
+            .alloc
+            .call(&mut *store, bytes.len() as i32)
+            .map_err(|error| trapped("alloc", error))?;
+        self.memory
+            .write(&mut *store, address(ptr) as usize, bytes)
+            .map_err(|error| error.to_string())?;
+        Ok(ptr)
+    }
+
+    fn read(&self, store: &Store<Host>, ptr: i64, len: i64) -> Result<Vec<u8>, String> {
+        let data = self.memory.data(store);
+        Ok(data[checked_range(data.len(), ptr, len)?].to_vec())
+    }
+}
+
+fn store(engine: &Engine, host: Host, fuel: u64) -> Store<Host> {
+    let mut store = Store::new(engine, host);
+    store.add_fuel(fuel).unwrap();
+    store
+}
+
+fn missing_export(export: &str) -> String {
+    format!("The contract does not export `{}`", export)
+}
+
+fn trapped(export: &str, error: impl Into<Error>) -> String {
+    match error.into() {
+        Error::Trap(trap) if matches!(trap.trap_code(), Some(TrapCode::OutOfFuel)) => {
+            format!("`{}` ran out of fuel", export)
+        }
+        error => format!("`{}` trapped: {}", export, error),
+    }
+}
+
+/// The `ext` module imported by contracts.
+fn linker(engine: &Engine) -> Linker<Host> {
+    let mut linker = Linker::new(engine);
+    linker
+        .func_wrap(
+            "ext",
+            "read_context_into_address",
+            |mut caller: Caller<Host>, addr: i64, len: i32| -> Result<i32, Trap> {
+                if len < 0 {
+                    return Err(Trap::new(format!("Negative length {}", len)));
+                }
+                let context = caller.data().context.clone();
+                if context.len() <= len as usize {
+                    memory_of(&caller)?
+                        .write(&mut caller, addr as usize, &context)
+                        .map_err(|error| Trap::new(error.to_string()))?;
+                }
+                Ok(context.len() as i32)
+            },
+        )
+        .unwrap();
+    linker
+        .func_wrap(
+            "ext",
+            "call_named",
+            |mut caller: Caller<Host>,
+             name_ptr: i64,
+             name_len: i32,
+             rpc_ptr: i64,
+             rpc_len: i32|
+             -> Result<(), Trap> {
+                let name = read_bytes(&caller, name_ptr, name_len)?;
+                let name = String::from_utf8(name).map_err(|error| Trap::new(error.to_string()))?;
+                let rpc = read_bytes(&caller, rpc_ptr, rpc_len)?;
+                caller.data_mut().calls.push(Call { name, rpc });
+                Ok(())
+            },
+        )
+        .unwrap();
+    linker
+        .func_wrap(
+            "ext",
+            "log_external",
+            |mut caller: Caller<Host>, message_ptr: i64, message_len: i32| -> Result<(), Trap> {
+                let message = read_bytes(&caller, message_ptr, message_len)?;
+                let message = String::from_utf8_lossy(&message).into_owned();
+                caller.data_mut().logs.push(message);
+                Ok(())
+            },
+        )
+        .unwrap();
+    linker
+}
+
+fn memory_of(caller: &Caller<Host>) -> Result<Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new("The contract does not export its memory"))
+}
+
+/// The address of the wasm32 pointer `ptr`, which is unsigned.
+fn address(ptr: i32) -> i64 {
+    i64::from(ptr as u32)
+}
+
+/// The `len` bytes at `ptr`, trapping if they are not within the memory of the contract.
+fn read_bytes(caller: &Caller<Host>, ptr: i64, len: i32) -> Result<Vec<u8>, Trap> {
+    let data = memory_of(caller)?.data(caller);
+    let range = checked_range(data.len(), ptr, len.into()).map_err(Trap::new)?;
+    Ok(data[range].to_vec())
+}
+
+/// The range of the `len` bytes at `ptr` in a memory of `size` bytes, or an error if the length
+/// is negative or the bytes are not within the memory.
+fn checked_range(size: usize, ptr: i64, len: i64) -> Result<Range<usize>, String> {
+    if len < 0 {
+        return Err(format!("Negative length {}", len));
+    }
+    match ptr.checked_add(len) {
+        Some(end) if ptr >= 0 && end as u64 <= size as u64 => Ok(ptr as usize..end as usize),
+        _ => Err(format!(
+            "The {} bytes at {} are out of the bounds of the memory of {} bytes",
+            len, ptr, size
+        )),
+    }
+}
@@ -0,0 +1,628 @@
+//! Local execution of compiled contracts.
+//!
+//! The [`Simulator`] is a local chain running the wasm modules built with
+//! `--target wasm32-unknown-unknown` on a pure-Rust interpreter. It serves the `ext` imports the
+//! way the chain does, and keeps the state of every deployed contract between transactions:
+//!
+//! ```ignore
+//! let wasm = std::fs::read("target/wasm32-unknown-unknown/release/token_contract.wasm")?;
+//! let mut simulator = Simulator::new();
+//! let token = simulator
+//!     .deploy("token", &wasm, owner, (None::<TokenContractState>,))?
+//!     .contract;
+//!
+//! let receipts = simulator.run_script(&[
+//!     Transaction::new(owner, token, "mint", (100u64,)),
+//!     Transaction::new(owner, token, "transfer", (alice, 30u64)),
+//! ])?;
+//! assert!(receipts.iter().all(|receipt| receipt.result.is_ok()));
+//! ```
+//!
+//! # Interactions
+//!
+//! Every contract is deployed under a name, which other contracts call it by with
+//! `call_named(name, rpc)`, `rpc` being the shortname of an action followed by its arguments.
+//! The calls of a successful invocation are executed after it, with the calling contract as the
+//! sender, in the order they were made and after the calls made before them. A call to a name
+//! no contract is deployed under is not executed.
+//!
+//! Once a call has been executed, the calling contract is called back if it has an action
+//! named `callback`, with the arguments `(name: String, error: Option<String>)`: the name the
+//! call was made to, and the [message](Failure::message) of the failure if the call failed. A
+//! failed call does not revert the invocation that made it.
+//!
+//! # Blocks and transactions
+//!
+//! Every transaction, call and callback gets a distinct transaction hash, and the calls and
+//! callbacks spawned by a transaction have its hash as their original transaction. They all
+//! happen in the current block, which only moves when advanced with
+//! [`Simulator::advance_blocks`].
+//!
+//! # Metering
+//!
+//! Every invocation is metered with the fuel of the interpreter, roughly the number of executed
+//! instructions, which its receipt reports. An invocation consuming more than the fuel limit of
+//! the simulator is aborted and fails. Calls and callbacks each get their own limit.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use pbc_contract_common::abi::{ContractAbi, FunctionKind};
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::hash::Hash;
+use pbc_contract_common::serialization::ReadWrite;
+use sha2::{Digest, Sha256};
+use wasmi::{Engine, Module};
+
+mod host;
+mod result;
+
+/// Milliseconds between the production of two blocks.
+pub const BLOCK_INTERVAL_MILLIS: i64 = 1000;
+
+/// The fuel limit of every invocation, unless changed with [`Simulator::set_fuel_limit`].
+pub const DEFAULT_FUEL_LIMIT: u64 = 1_000_000_000;
+
+/// The most calls and callbacks a transaction may spawn. Those beyond fail without executing.
+pub const MAX_SPAWNED: usize = 1000;
+
+/// A call made by a contract with `call_named`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub name: String,
+    pub rpc: Vec<u8>,
+}
+
+/// An invocation of the action `action` of the contract at `contract`, sent by `sender`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub sender: Address,
+    pub contract: Address,
+    pub action: String,
+    /// The serialized arguments of the action.
+    pub arguments: Vec<u8>,
+}
+
+impl Transaction {
+    /// A transaction with `arguments` given as a tuple in declaration order.
+    pub fn new<A: ReadWrite>(
+        sender: Address,
+        contract: Address,
+        action: &str,
+        arguments: A,
+    ) -> Self {
+        Transaction {
+            sender,
+            contract,
+            action: action.to_string(),
+            arguments: encode(&arguments),
+        }
+    }
+}
+
+/// Why an invocation failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// The contract returned an error, serialized with the error type of the function.
+    Error(Vec<u8>),
+    /// The execution trapped or could not take place, for the given reason.
+    Aborted(String),
+}
+
+impl Failure {
+    /// The error returned by the contract read as `E`, or `None` if the invocation was aborted.
+    pub fn error<E: ReadWrite>(&self) -> Option<E> {
+        match self {
+            Failure::Error(error) => Some(E::read_from(&mut error.as_slice())),
+            Failure::Aborted(_) => None,
+        }
+    }
+
+    /// The reason of an abort, or the error of the contract if it is a `String`. Other errors
+    /// are described by their bytes.
+    pub fn message(&self) -> String {
+        match self {
+            Failure::Error(error) => match error.split_at_checked(4) {
+                Some((len, message))
+                    if u32::from_be_bytes(len.try_into().unwrap()) as usize == message.len() =>
+                {
+                    String::from_utf8(message.to_vec())
+                        .unwrap_or_else(|_| format!("The contract failed with {:02x?}", error))
+                }
+                _ => format!("The contract failed with {:02x?}", error),
+            },
+            Failure::Aborted(reason) => reason.clone(),
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(reason: String) -> Self {
+        Failure::Aborted(reason)
+    }
+}
+
+/// The outcome of a transaction, or of a call or callback it spawned.
+///
+/// A failed invocation is reverted: it leaves the state untouched and has no events or calls,
+/// but keeps the messages logged and the fuel consumed before the failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    /// The invoked contract.
+    pub contract: Address,
+    pub transaction: Hash,
+    /// The serialized error of the contract, or the reason the execution was aborted.
+    pub result: Result<(), Failure>,
+    /// The id and serialized payload of every emitted event, in emission order.
+    pub events: Vec<(u32, Vec<u8>)>,
+    pub logs: Vec<String>,
+    pub calls: Vec<Call>,
+    /// The fuel consumed by the invocation, including the copying of its inputs into the
+    /// memory of the contract.
+    pub fuel: u64,
+    /// The receipts of the calls and callbacks spawned by a transaction, in execution order.
+    /// Always empty in the receipts of calls and callbacks.
+    pub spawned: Vec<Receipt>,
+}
+
+impl Receipt {
+    /// The fuel consumed by the invocation and the calls and callbacks it spawned.
+    pub fn total_fuel(&self) -> u64 {
+        self.fuel + self.spawned.iter().map(Receipt::total_fuel).sum::<u64>()
+    }
+}
+
+struct Contract {
+    name: String,
+    module: Module,
+    abi: ContractAbi,
+    owner: Address,
+    state: Vec<u8>,
+}
+
+/// A call or callback waiting to be executed.
+enum Interaction {
+    /// The call of `caller` to `name`, the name of the contract at `callee`.
+    Call {
+        caller: Address,
+        callee: Address,
+        name: String,
+        rpc: Vec<u8>,
+    },
+    /// The callback of `caller`, with its serialized arguments.
+    Callback {
+        caller: Address,
+        shortname: u32,
+        arguments: Vec<u8>,
+    },
+}
+
+/// The transaction hashes of an invocation.
+#[derive(Clone, Copy)]
+struct Transactions {
+    current: Hash,
+    original: Hash,
+}
+
+/// A local chain of deployed contracts, each with its own state.
+pub struct Simulator {
+    engine: Engine,
+    contracts: BTreeMap<Address, Contract>,
+    block_time: i64,
+    transactions: u64,
+    fuel_limit: u64,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Simulator {
+            engine: host::engine(),
+            contracts: BTreeMap::new(),
+            block_time: 1,
+            transactions: 0,
+            fuel_limit: DEFAULT_FUEL_LIMIT,
+        }
+    }
+}
+
+impl Simulator {
+    pub fn new() -> Self {
+        Simulator::default()
+    }
+
+    /// Deploy the contract module `wasm` under `name`, initialized with `arguments` by `owner`.
+    ///
+    /// The contract gets a `PublicContract` address derived from the hash of the deployment,
+    /// which is the contract of the receipt. It is only deployed if its initializer succeeds.
+    pub fn deploy<A: ReadWrite>(
+        &mut self,
+        name: &str,
+        wasm: &[u8],
+        owner: Address,
+        arguments: A,
+    ) -> Result<Receipt, String> {
+        if self.address_of(name).is_some() {
+            return Err(format!("A contract named `{}` is already deployed", name));
+        }
+        let module = Module::new(&self.engine, wasm)
+            .map_err(|error| format!("Invalid contract module: {}", error))?;
+        let abi = host::read_abi(&self.engine, &module, self.fuel_limit)?;
+        let abi = ContractAbi::try_from_binary(&abi)
+            .map_err(|error| format!("Invalid contract ABI: {}", error))?;
+
+        let transactions = self.next_transaction(None);
+        let mut identifier = [0u8; 20];
+        identifier.copy_from_slice(&transactions.current[..20]);
+        let address = Address::PublicContract(identifier);
+
+        let context = self.context(owner, address, owner, transactions);
+        let rpc = encode(&arguments);
+        let execution = host::execute(
+            &self.engine,
+            &module,
+            "init",
+            &[&context, &rpc],
+            self.fuel_limit,
+        );
+        let (mut receipt, state) = receipt(address, transactions.current, execution);
+        if let Some(state) = state {
+            let contract = Contract {
+                name: name.to_string(),
+                module,
+                abi,
+                owner,
+                state,
+            };
+            self.contracts.insert(address, contract);
+            receipt.spawned = self.run_spawned(&receipt);
+        }
+        Ok(receipt)
+    }
+
+    /// Execute `transaction` and the calls and callbacks it spawns, keeping the new state of
+    /// every contract whose invocation succeeds.
+    ///
+    /// Fails if there is no such contract or action.
+    pub fn execute(&mut self, transaction: &Transaction) -> Result<Receipt, String> {
+        let contract = self.contract(transaction.contract)?;
+        let shortname = shortname(&contract.abi, FunctionKind::Action, &transaction.action)?;
+
+        let transactions = self.next_transaction(None);
+        let mut receipt = self.invoke(
+            transaction.contract,
+            transaction.sender,
+            shortname,
+            &transaction.arguments,
+            transactions,
+        );
+        receipt.spawned = self.run_spawned(&receipt);
+        Ok(receipt)
+    }
+
+    /// Execute the transactions of `script` in order, stopping at the first that cannot be
+    /// executed. Failed transactions do not stop the script.
+    pub fn run_script(&mut self, script: &[Transaction]) -> Result<Vec<Receipt>, String> {
+        script
+            .iter()
+            .map(|transaction| self.execute(transaction))
+            .collect()
+    }
+
+    /// Invoke the view `name` of the contract at `address` with `arguments`, as its owner.
+    ///
+    /// Views are not transactions, and see the zero hash as their transactions.
+    pub fn view<A: ReadWrite, V: ReadWrite>(
+        &self,
+        address: Address,
+        name: &str,
+        arguments: A,
+    ) -> Result<V, Failure> {
+        let contract = self.contract(address)?;
+        let shortname = shortname(&contract.abi, FunctionKind::View, name)?;
+
+        let transactions = Transactions {
+            current: [0u8; 32],
+            original: [0u8; 32],
+        };
+        let context = self.context(contract.owner, address, contract.owner, transactions);
+        let export = format!("view_{:x}", shortname);
+        let rpc = encode(&arguments);
+        let execution = host::execute(
+            &self.engine,
+            &contract.module,
+            &export,
+            &[&context, &contract.state, &rpc],
+            self.fuel_limit,
+        );
+        match result::parse_value_result(&execution.result?)? {
+            Ok(value) => Ok(V::read_from(&mut value.as_slice())),
+            Err(error) => Err(Failure::Error(error)),
+        }
+    }
+
+    /// The serialized state of the contract at `address`.
+    pub fn state(&self, address: Address) -> Option<&[u8]> {
+        self.contracts
+            .get(&address)
+            .map(|contract| contract.state.as_slice())
+    }
+
+    /// The ABI exported by the contract at `address`.
+    pub fn abi(&self, address: Address) -> Option<&ContractAbi> {
+        self.contracts.get(&address).map(|contract| &contract.abi)
+    }
+
+    /// The address of the contract deployed under `name`.
+    pub fn address_of(&self, name: &str) -> Option<Address> {
+        self.contracts
+            .iter()
+            .find(|(_, contract)| contract.name == name)
+            .map(|(address, _)| *address)
+    }
+
+    pub fn block_time(&self) -> i64 {
+        self.block_time
+    }
+
+    /// The production time of the current block, in milliseconds.
+    pub fn block_production_time(&self) -> i64 {
+        self.block_time * BLOCK_INTERVAL_MILLIS
+    }
+
+    pub fn fuel_limit(&self) -> u64 {
+        self.fuel_limit
+    }
+
+    /// Abort the following invocations once they consume more than `limit` fuel.
+    pub fn set_fuel_limit(&mut self, limit: u64) {
+        self.fuel_limit = limit;
+    }
+
+    /// Move the following transactions `blocks` ahead.
+    pub fn advance_blocks(&mut self, blocks: i64) {
+        self.block_time += blocks;
+    }
+
+    /// Execute the calls and callbacks spawned by the transaction of `receipt`.
+    fn run_spawned(&mut self, receipt: &Receipt) -> Vec<Receipt> {
+        let mut pending = VecDeque::new();
+        self.spawn(&mut pending, receipt);
+
+        let mut spawned = Vec::new();
+        while let Some(interaction) = pending.pop_front() {
+            let transactions = self.next_transaction(Some(receipt.transaction));
+            let executed = match interaction {
+                Interaction::Call {
+                    caller,
+                    callee,
+                    name,
+                    rpc,
+                } => {
+                    let executed = if spawned.len() < MAX_SPAWNED {
+                        self.call(caller, callee, &rpc, transactions)
+                    } else {
+                        too_many_spawned(callee, transactions.current)
+                    };
+                    self.spawn(&mut pending, &executed);
+                    let error = executed.result.as_ref().err().map(Failure::message);
+                    self.call_back(&mut pending, caller, &name, error);
+                    executed
+                }
+                Interaction::Callback {
+                    caller,
+                    shortname,
+                    arguments,
+                } => {
+                    let executed = if spawned.len() < MAX_SPAWNED {
+                        self.invoke(caller, caller, shortname, &arguments, transactions)
+                    } else {
+                        too_many_spawned(caller, transactions.current)
+                    };
+                    self.spawn(&mut pending, &executed);
+                    executed
+                }
+            };
+            spawned.push(executed);
+        }
+        spawned
+    }
+
+    /// Queue the calls made in `receipt`.
+    fn spawn(&self, pending: &mut VecDeque<Interaction>, receipt: &Receipt) {
+        for call in &receipt.calls {
+            match self.address_of(&call.name) {
+                Some(callee) => pending.push_back(Interaction::Call {
+                    caller: receipt.contract,
+                    callee,
+                    name: call.name.clone(),
+                    rpc: call.rpc.clone(),
+                }),
+                None => {
+                    let error = format!("No contract is named `{}`", call.name);
+                    self.call_back(pending, receipt.contract, &call.name, Some(error));
+                }
+            }
+        }
+    }
+
+    /// Queue the callback of `caller` for its call to `name`, if it has a callback.
+    fn call_back(
+        &self,
+        pending: &mut VecDeque<Interaction>,
+        caller: Address,
+        name: &str,
+        error: Option<String>,
+    ) {
+        let abi = &self.contracts[&caller].abi;
+        if let Ok(shortname) = shortname(abi, FunctionKind::Action, "callback") {
+            pending.push_back(Interaction::Callback {
+                caller,
+                shortname,
+                arguments: encode(&(name.to_string(), error)),
+            });
+        }
+    }
+
+    /// Execute the call `rpc` of `caller` to `callee`.
+    fn call(
+        &mut self,
+        caller: Address,
+        callee: Address,
+        rpc: &[u8],
+        transactions: Transactions,
+    ) -> Receipt {
+        if rpc.len() < 4 {
+            let error = String::from("The call has no action shortname");
+            return failed(callee, transactions.current, error.into(), Vec::new());
+        }
+        let shortname = u32::from_be_bytes(rpc[..4].try_into().unwrap());
+        self.invoke(callee, caller, shortname, &rpc[4..], transactions)
+    }
+
+    /// Invoke the action `shortname` of the contract at `address`, keeping its new state if it
+    /// succeeds.
+    fn invoke(
+        &mut self,
+        address: Address,
+        sender: Address,
+        shortname: u32,
+        arguments: &[u8],
+        transactions: Transactions,
+    ) -> Receipt {
+        let contract = &self.contracts[&address];
+        let context = self.context(contract.owner, address, sender, transactions);
+        let export = format!("action_{:x}", shortname);
+        let execution = host::execute(
+            &self.engine,
+            &contract.module,
+            &export,
+            &[&context, &contract.state, arguments],
+            self.fuel_limit,
+        );
+        let (receipt, state) = receipt(address, transactions.current, execution);
+        if let Some(state) = state {
+            self.contracts.get_mut(&address).unwrap().state = state;
+        }
+        receipt
+    }
+
+    fn contract(&self, address: Address) -> Result<&Contract, String> {
+        self.contracts
+            .get(&address)
+            .ok_or_else(|| format!("No contract is deployed at {:?}", address))
+    }
+
+    /// The hashes of a new transaction, spawned by `original` if any.
+    fn next_transaction(&mut self, original: Option<Hash>) -> Transactions {
+        self.transactions += 1;
+        let mut current = [0u8; 32];
+        current.copy_from_slice(&Sha256::digest(&self.transactions.to_be_bytes()));
+        Transactions {
+            current,
+            original: original.unwrap_or(current),
+        }
+    }
+
+    fn context(
+        &self,
+        owner: Address,
+        contract: Address,
+        sender: Address,
+        transactions: Transactions,
+    ) -> Vec<u8> {
+        encode(&ContractContext {
+            owner,
+            contract_address: contract,
+            sender,
+            block_time: self.block_time,
+            block_production_time: self.block_production_time(),
+            current_transaction: transactions.current,
+            original_transaction: transactions.original,
+        })
+    }
+}
+
+/// The shortname of the function `name` of kind `kind` in `abi`.
+fn shortname(abi: &ContractAbi, kind: FunctionKind, name: &str) -> Result<u32, String> {
+    abi.functions
+        .iter()
+        .find(|function| function.kind == kind && function.name == name)
+        .and_then(|function| function.shortname)
+        .ok_or_else(|| {
+            let kind = match kind {
+                FunctionKind::Init => "initializer",
+                FunctionKind::Action => "action",
+                FunctionKind::View => "view",
+            };
+            format!("The contract has no {} `{}`", kind, name)
+        })
+}
+
+/// The receipt of an execution of an action or initializer, and the new state if it succeeded.
+fn receipt(
+    contract: Address,
+    transaction: Hash,
+    execution: host::Execution,
+) -> (Receipt, Option<Vec<u8>>) {
+    let fuel = execution.fuel;
+    let result = match execution.result {
+        Ok(result) => result,
+        Err(trap) => {
+            let receipt = failed(contract, transaction, trap.into(), execution.logs);
+            return (Receipt { fuel, ..receipt }, None);
+        }
+    };
+    let result = match result::parse_result(&result) {
+        Ok(result) => result,
+        Err(malformed) => {
+            let receipt = failed(contract, transaction, malformed.into(), execution.logs);
+            return (Receipt { fuel, ..receipt }, None);
+        }
+    };
+    match result {
+        Ok(invocation) => {
+            let receipt = Receipt {
+                contract,
+                transaction,
+                result: Ok(()),
+                events: invocation.events,
+                logs: execution.logs,
+                calls: execution.calls,
+                fuel,
+                spawned: Vec::new(),
+            };
+            (receipt, Some(invocation.state))
+        }
+        Err(error) => {
+            let receipt = failed(contract, transaction, Failure::Error(error), execution.logs);
+            (Receipt { fuel, ..receipt }, None)
+        }
+    }
+}
+
+fn failed(contract: Address, transaction: Hash, error: Failure, logs: Vec<String>) -> Receipt {
+    Receipt {
+        contract,
+        transaction,
+        result: Err(error),
+        events: Vec::new(),
+        logs,
+        calls: Vec::new(),
+        fuel: 0,
+        spawned: Vec::new(),
+    }
+}
+
+fn too_many_spawned(contract: Address, transaction: Hash) -> Receipt {
+    let error = format!(
+        "The transaction spawned more than {} interactions",
+        MAX_SPAWNED
+    );
+    failed(contract, transaction, error.into(), Vec::new())
+}
+
+fn encode<T: ReadWrite>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.write_to(&mut bytes).unwrap();
+    bytes
+}
@@ -0,0 +1,82 @@
+//! Parsing of the result buffers handed back by contracts.
+//!
+//! The buffers follow the layout of `pbc_contract_common::result`, but are written by untrusted
+//! modules, so they are parsed without panicking. A malformed buffer is reported as the reason
+//! the invocation was aborted.
+
+use pbc_contract_common::result::{Invocation, STATUS_ERROR, STATUS_OK};
+
+/// The new state and events of a successful action or initializer, or the serialized error of
+/// a failed one, or why the buffer is malformed.
+pub(crate) fn parse_result(result: &[u8]) -> Result<Result<Invocation, Vec<u8>>, String> {
+    let mut reader = Reader { bytes: result };
+    if let Err(error) = reader.status()? {
+        return Ok(Err(error));
+    }
+    let state = reader.bytes()?;
+    let count = reader.u32()?;
+    let mut events = Vec::new();
+    for _ in 0..count {
+        events.push((reader.u32()?, reader.bytes()?));
+    }
+    reader.end()?;
+    Ok(Ok(Invocation { state, events }))
+}
+
+/// The serialized value of a successful view, or the serialized error of a failed one, or why
+/// the buffer is malformed.
+pub(crate) fn parse_value_result(result: &[u8]) -> Result<Result<Vec<u8>, Vec<u8>>, String> {
+    let mut reader = Reader { bytes: result };
+    if let Err(error) = reader.status()? {
+        return Ok(Err(error));
+    }
+    let value = reader.bytes()?;
+    reader.end()?;
+    Ok(Ok(value))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err(String::from("The result buffer ends unexpectedly"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// A length-prefixed byte vector.
+    fn bytes(&mut self) -> Result<Vec<u8>, String> {
+        let len = self.u32()?;
+        Ok(self.take(len as usize)?.to_vec())
+    }
+
+    /// `Ok` for a successful invocation, or the serialized error of a failed one, which ends the
+    /// buffer.
+    fn status(&mut self) -> Result<Result<(), Vec<u8>>, String> {
+        match self.take(1)?[0] {
+            STATUS_OK => Ok(Ok(())),
+            STATUS_ERROR => {
+                let error = self.bytes()?;
+                self.end()?;
+                Ok(Err(error))
+            }
+            status => Err(format!("Unrecognized result status {}", status)),
+        }
+    }
+
+    fn end(&self) -> Result<(), String> {
+        match self.bytes.len() {
+            0 => Ok(()),
+            len => Err(format!("The result buffer has {} trailing bytes", len)),
+        }
+    }
+}
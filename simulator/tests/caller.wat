@@ -0,0 +1,85 @@
+;; A contract calling the contract named "counter", with the state a big-endian u64 whose last
+;; byte counts the callbacks, and whose byte before it is 1 if the last call failed.
+;;
+;; `{abi}` and `{abi_len}` are replaced by the binary ABI of the contract before compiling.
+(module
+  (import "ext" "call_named" (func $call_named (param i64 i32 i64 i32)))
+
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 4096))
+
+  (data (i32.const 0) "counter")
+  (data (i32.const 8) "missing")
+  ;; The RPC of `increment`, followed by its argument, and of `fail`.
+  (data (i32.const 16) "\00\00\00\01")
+  (data (i32.const 20) "\00\00\00\02")
+  ;; Return descriptor of a successful invocation with the state 0 and no events.
+  (data (i32.const 32) "\00\00\00\11\00\00\00\00\08\00\00\00\00\00\00\00\00\00\00\00\00")
+  (data (i32.const 1024) "{abi}")
+
+  (func $alloc (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "dealloc") (param i32 i32))
+
+  ;; The return descriptor of a successful invocation keeping `$state`.
+  (func $ok (param $state i32) (result i32)
+    (local $result i32)
+    (local.set $result (call $alloc (i32.const 21)))
+    (memory.copy (local.get $result) (i32.const 32) (i32.const 21))
+    (if (local.get $state)
+      (then (memory.copy (i32.add (local.get $result) (i32.const 9)) (local.get $state) (i32.const 8))))
+    (local.get $result))
+
+  (func (export "export_contract_schema_binary") (param $dst i32) (param $len i32) (result i32)
+    (if (i32.le_u (i32.const {abi_len}) (local.get $len))
+      (then (memory.copy (local.get $dst) (i32.const 1024) (i32.const {abi_len}))))
+    (i32.const {abi_len}))
+
+  (func (export "init") (param i32 i32 i32 i32) (result i32)
+    (call $ok (i32.const 0)))
+
+  ;; forward(by: u64), calling `increment(by)` of the counter.
+  (func (export "action_1")
+    (param $ctx i32) (param $ctx_len i32)
+    (param $state i32) (param $state_len i32)
+    (param $rpc i32) (param $rpc_len i32)
+    (result i32)
+    (local $call i32)
+    (local.set $call (call $alloc (i32.const 12)))
+    (memory.copy (local.get $call) (i32.const 16) (i32.const 4))
+    (memory.copy (i32.add (local.get $call) (i32.const 4)) (local.get $rpc) (i32.const 8))
+    (call $call_named
+      (i64.const 0) (i32.const 7)
+      (i64.extend_i32_u (local.get $call)) (i32.const 12))
+    (call $ok (local.get $state)))
+
+  ;; callback(name: String, error: Option<String>)
+  (func (export "action_2")
+    (param $ctx i32) (param $ctx_len i32)
+    (param $state i32) (param $state_len i32)
+    (param $rpc i32) (param $rpc_len i32)
+    (result i32)
+    (local $result i32)
+    (local.set $result (call $ok (local.get $state)))
+    (i32.store8 offset=16 (local.get $result)
+      (i32.add (i32.load8_u offset=7 (local.get $state)) (i32.const 1)))
+    ;; The option follows the name, whose length is below 256.
+    (i32.store8 offset=15 (local.get $result)
+      (i32.load8_u offset=4
+        (i32.add (local.get $rpc) (i32.load8_u offset=3 (local.get $rpc)))))
+    (local.get $result))
+
+  ;; forward_failing(), calling `fail()` of the counter.
+  (func (export "action_3") (param i32 i32) (param $state i32) (param i32 i32 i32) (result i32)
+    (call $call_named (i64.const 0) (i32.const 7) (i64.const 20) (i32.const 4))
+    (call $ok (local.get $state)))
+
+  ;; forward_missing(), calling a contract that is not deployed.
+  (func (export "action_4") (param i32 i32) (param $state i32) (param i32 i32 i32) (result i32)
+    (call $call_named (i64.const 8) (i32.const 7) (i64.const 16) (i32.const 4))
+    (call $ok (local.get $state)))
+)
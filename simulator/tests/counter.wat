@@ -0,0 +1,125 @@
+;; A counter contract following the protocol of the exports generated by `#[contract]`,
+;; with the state a big-endian u64 below 256.
+;;
+;; `{abi}` and `{abi_len}` are replaced by the binary ABI of the contract before compiling.
+(module
+  (import "ext" "read_context_into_address" (func $read_context (param i64 i32) (result i32)))
+  (import "ext" "call_named" (func $call_named (param i64 i32 i64 i32)))
+  (import "ext" "log_external" (func $log (param i64 i32)))
+
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 4096))
+
+  (data (i32.const 0) "initialized")
+  (data (i32.const 16) "context")
+  (data (i32.const 24) "failing")
+  ;; Return descriptor of a successful invocation with the state 0 and no events.
+  (data (i32.const 32) "\00\00\00\11\00\00\00\00\08\00\00\00\00\00\00\00\00\00\00\00\00")
+  ;; Return descriptor of an invocation failing with "boom".
+  (data (i32.const 64) "\00\00\00\0d\01\00\00\00\08\00\00\00\04boom")
+  ;; Return descriptor of a view returning the u64 0.
+  (data (i32.const 96) "\00\00\00\0d\00\00\00\00\08\00\00\00\00\00\00\00\00")
+  ;; Malformed return descriptors: an empty result, an unknown status, and a truncated state.
+  (data (i32.const 128) "\00\00\00\00")
+  (data (i32.const 136) "\00\00\00\01\07")
+  (data (i32.const 144) "\00\00\00\03\00\00\00")
+  (data (i32.const 1024) "{abi}")
+
+  (func $alloc (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "dealloc") (param i32 i32))
+
+  (func $copy (param $src i32) (param $len i32) (result i32)
+    (local $dst i32)
+    (local.set $dst (call $alloc (local.get $len)))
+    (memory.copy (local.get $dst) (local.get $src) (local.get $len))
+    (local.get $dst))
+
+  (func (export "export_contract_schema_binary") (param $dst i32) (param $len i32) (result i32)
+    (if (i32.le_u (i32.const {abi_len}) (local.get $len))
+      (then (memory.copy (local.get $dst) (i32.const 1024) (i32.const {abi_len}))))
+    (i32.const {abi_len}))
+
+  (func (export "init") (param i32 i32 i32 i32) (result i32)
+    (call $log (i64.const 0) (i32.const 11))
+    (call $copy (i32.const 32) (i32.const 21)))
+
+  ;; increment(by: u64), which also calls `context` with the context read from the host.
+  (func (export "action_1")
+    (param $ctx i32) (param $ctx_len i32)
+    (param $state i32) (param $state_len i32)
+    (param $rpc i32) (param $rpc_len i32)
+    (result i32)
+    (local $buffer i32)
+    (local $result i32)
+    (local.set $buffer (call $alloc (i32.const 256)))
+    (call $call_named
+      (i64.const 16) (i32.const 7)
+      (i64.extend_i32_u (local.get $buffer))
+      (call $read_context (i64.extend_i32_u (local.get $buffer)) (i32.const 256)))
+    (local.set $result (call $copy (i32.const 32) (i32.const 21)))
+    (i32.store8 offset=16 (local.get $result)
+      (i32.add
+        (i32.load8_u offset=7 (local.get $state))
+        (i32.load8_u offset=7 (local.get $rpc))))
+    (local.get $result))
+
+  ;; fail(), failing with "boom" after logging.
+  (func (export "action_2") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (call $log (i64.const 24) (i32.const 7))
+    (call $copy (i32.const 64) (i32.const 17)))
+
+  ;; crash(), panicking.
+  (func (export "action_3") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (unreachable))
+
+  ;; count() -> u64
+  (func (export "view_4")
+    (param $ctx i32) (param $ctx_len i32)
+    (param $state i32) (param $state_len i32)
+    (param $rpc i32) (param $rpc_len i32)
+    (result i32)
+    (local $result i32)
+    (local.set $result (call $copy (i32.const 96) (i32.const 17)))
+    (i32.store8 offset=16 (local.get $result) (i32.load8_u offset=7 (local.get $state)))
+    (local.get $result))
+
+  ;; log_negative(), logging a message of negative length.
+  (func (export "action_5") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (call $log (i64.const 0) (i32.const -1))
+    (call $copy (i32.const 32) (i32.const 21)))
+
+  ;; log_out_of_bounds(), logging a message running past the end of the memory.
+  (func (export "action_6") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (call $log (i64.const 65530) (i32.const 100))
+    (call $copy (i32.const 32) (i32.const 21)))
+
+  ;; read_context_negative(), reading the context into a buffer of negative length.
+  (func (export "action_7") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (drop (call $read_context (i64.const 0) (i32.const -1)))
+    (call $copy (i32.const 32) (i32.const 21)))
+
+  ;; return_out_of_bounds(), returning a descriptor past the end of the memory.
+  (func (export "action_8") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (i32.const 65534))
+
+  ;; return_empty(), returning an empty result buffer.
+  (func (export "action_9") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (call $copy (i32.const 128) (i32.const 4)))
+
+  ;; return_unknown_status(), returning a result with an unknown status.
+  (func (export "action_a") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (call $copy (i32.const 136) (i32.const 5)))
+
+  ;; return_truncated(), returning a result whose state length is cut short.
+  (func (export "action_b") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (call $copy (i32.const 144) (i32.const 7)))
+
+  ;; return_high_address(), returning a descriptor at 2 GiB.
+  (func (export "action_c") (param i32 i32 i32 i32 i32 i32) (result i32)
+    (i32.const 0x80000000))
+)
@@ -0,0 +1,453 @@
+use pbc_contract_common::abi::{
+    ArgumentAbi, ContractAbi, FunctionAbi, FunctionKind, SchemaAbi, ABI_VERSION,
+};
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::serialization::ReadWrite;
+use pbc_simulator::{Failure, Receipt, Simulator, Transaction, BLOCK_INTERVAL_MILLIS};
+
+const OWNER: Address = Address::Account([1u8; 20]);
+const ALICE: Address = Address::Account([2u8; 20]);
+
+fn function(kind: FunctionKind, name: &str, shortname: Option<u32>) -> FunctionAbi {
+    FunctionAbi {
+        kind,
+        name: name.to_string(),
+        shortname,
+        arguments: Vec::new(),
+        returns: None,
+        error: None,
+    }
+}
+
+fn argument(name: &str, schema: SchemaAbi) -> ArgumentAbi {
+    ArgumentAbi {
+        name: name.to_string(),
+        schema,
+    }
+}
+
+/// The contract of the WAT `source`, with the ABI of `functions` embedded.
+fn wasm(source: &str, functions: Vec<FunctionAbi>) -> Vec<u8> {
+    let abi = ContractAbi {
+        version: ABI_VERSION,
+        state: SchemaAbi::of::<u64>(),
+        functions,
+        events: Vec::new(),
+        upgrade: None,
+    }
+    .as_binary();
+
+    let escaped: String = abi.iter().map(|byte| format!("\\{:02x}", byte)).collect();
+    let source = source
+        .replace("{abi_len}", &abi.len().to_string())
+        .replace("{abi}", &escaped);
+    wat::parse_str(source).unwrap()
+}
+
+/// The counter contract of `counter.wat`.
+fn counter_wasm() -> Vec<u8> {
+    let mut increment = function(FunctionKind::Action, "increment", Some(1));
+    increment
+        .arguments
+        .push(argument("by", SchemaAbi::of::<u64>()));
+    let mut count = function(FunctionKind::View, "count", Some(4));
+    count.returns = Some(SchemaAbi::of::<u64>());
+    let functions = vec![
+        function(FunctionKind::Init, "initialize", None),
+        increment,
+        function(FunctionKind::Action, "fail", Some(2)),
+        function(FunctionKind::Action, "crash", Some(3)),
+        count,
+        function(FunctionKind::Action, "log_negative", Some(5)),
+        function(FunctionKind::Action, "log_out_of_bounds", Some(6)),
+        function(FunctionKind::Action, "read_context_negative", Some(7)),
+        function(FunctionKind::Action, "return_out_of_bounds", Some(8)),
+        function(FunctionKind::Action, "return_empty", Some(9)),
+        function(FunctionKind::Action, "return_unknown_status", Some(0x0a)),
+        function(FunctionKind::Action, "return_truncated", Some(0x0b)),
+        function(FunctionKind::Action, "return_high_address", Some(0x0c)),
+    ];
+    wasm(include_str!("counter.wat"), functions)
+}
+
+/// The contract of `caller.wat`, calling the contract named "counter".
+fn caller_wasm() -> Vec<u8> {
+    let mut forward = function(FunctionKind::Action, "forward", Some(1));
+    forward
+        .arguments
+        .push(argument("by", SchemaAbi::of::<u64>()));
+    let mut callback = function(FunctionKind::Action, "callback", Some(2));
+    callback
+        .arguments
+        .push(argument("name", SchemaAbi::of::<String>()));
+    callback
+        .arguments
+        .push(argument("error", SchemaAbi::of::<Option<String>>()));
+    let functions = vec![
+        function(FunctionKind::Init, "initialize", None),
+        forward,
+        callback,
+        function(FunctionKind::Action, "forward_failing", Some(3)),
+        function(FunctionKind::Action, "forward_missing", Some(4)),
+    ];
+    wasm(include_str!("caller.wat"), functions)
+}
+
+fn deploy(simulator: &mut Simulator, name: &str, wasm: &[u8]) -> Address {
+    let receipt = simulator.deploy(name, wasm, OWNER, ()).unwrap();
+    assert_eq!(receipt.result, Ok(()));
+    receipt.contract
+}
+
+/// A simulator with the counter deployed, and the address of the counter.
+fn deployed() -> (Simulator, Address) {
+    let mut simulator = Simulator::new();
+    let counter = deploy(&mut simulator, "counter", &counter_wasm());
+    (simulator, counter)
+}
+
+fn count(simulator: &Simulator, contract: Address) -> u64 {
+    u64::read_from(&mut simulator.state(contract).unwrap())
+}
+
+/// The context `receipt` of an increment of the counter was invoked with.
+fn context(receipt: &Receipt) -> ContractContext {
+    assert_eq!(receipt.calls[0].name, "context");
+    ContractContext::read_from(&mut receipt.calls[0].rpc.as_slice())
+}
+
+#[test]
+fn deploy_runs_the_initializer() {
+    let mut simulator = Simulator::new();
+
+    let receipt = simulator
+        .deploy("counter", &counter_wasm(), OWNER, ())
+        .unwrap();
+
+    assert_eq!(receipt.result, Ok(()));
+    assert_eq!(receipt.logs, vec![String::from("initialized")]);
+    assert_eq!(simulator.address_of("counter"), Some(receipt.contract));
+    assert_eq!(count(&simulator, receipt.contract), 0);
+    assert_eq!(simulator.abi(receipt.contract).unwrap().functions.len(), 13);
+}
+
+#[test]
+fn deploy_rejects_a_taken_name() {
+    let (mut simulator, _) = deployed();
+
+    let error = simulator
+        .deploy("counter", &counter_wasm(), OWNER, ())
+        .unwrap_err();
+
+    assert_eq!(error, "A contract named `counter` is already deployed");
+}
+
+#[test]
+fn deploy_rejects_a_malformed_abi() {
+    let mut simulator = Simulator::new();
+    // The contract only exports the first 3 bytes of its ABI.
+    let source = include_str!("counter.wat").replace("{abi_len}", "3");
+    let wasm = wasm(&source, Vec::new());
+
+    let deployed = simulator.deploy("counter", &wasm, OWNER, ());
+
+    assert_eq!(
+        deployed,
+        Err(String::from(
+            "Invalid contract ABI: The ABI ends unexpectedly"
+        ))
+    );
+}
+
+#[test]
+fn contracts_get_distinct_public_contract_addresses() {
+    let (mut simulator, counter) = deployed();
+
+    let caller = deploy(&mut simulator, "caller", &caller_wasm());
+
+    assert!(matches!(counter, Address::PublicContract(_)));
+    assert!(matches!(caller, Address::PublicContract(_)));
+    assert_ne!(counter, caller);
+}
+
+#[test]
+fn script_keeps_the_state_between_transactions() {
+    let (mut simulator, counter) = deployed();
+
+    let receipts = simulator
+        .run_script(&[
+            Transaction::new(OWNER, counter, "increment", (3u64,)),
+            Transaction::new(ALICE, counter, "increment", (4u64,)),
+        ])
+        .unwrap();
+
+    assert!(receipts.iter().all(|receipt| receipt.result.is_ok()));
+    assert_eq!(count(&simulator, counter), 7);
+    assert_eq!(simulator.view::<_, u64>(counter, "count", ()), Ok(7));
+}
+
+#[test]
+fn contract_reads_the_context_from_the_host() {
+    let (mut simulator, counter) = deployed();
+
+    let receipt = simulator
+        .execute(&Transaction::new(ALICE, counter, "increment", (1u64,)))
+        .unwrap();
+
+    assert_eq!(receipt.calls.len(), 1);
+    let context = context(&receipt);
+    assert_eq!(context.owner, OWNER);
+    assert_eq!(context.contract_address, counter);
+    assert_eq!(context.sender, ALICE);
+    assert_eq!(context.current_transaction, receipt.transaction);
+    assert_eq!(context.original_transaction, receipt.transaction);
+}
+
+#[test]
+fn transactions_get_distinct_hashes() {
+    let (mut simulator, counter) = deployed();
+
+    let receipts = simulator
+        .run_script(&[
+            Transaction::new(OWNER, counter, "increment", (1u64,)),
+            Transaction::new(OWNER, counter, "increment", (1u64,)),
+        ])
+        .unwrap();
+
+    assert_ne!(receipts[0].transaction, receipts[1].transaction);
+    assert_ne!(receipts[0].transaction, [0u8; 32]);
+}
+
+#[test]
+fn blocks_only_move_when_advanced() {
+    let (mut simulator, counter) = deployed();
+    let increment = Transaction::new(OWNER, counter, "increment", (1u64,));
+
+    let before = simulator.execute(&increment).unwrap();
+    simulator.advance_blocks(3);
+    let after = simulator.execute(&increment).unwrap();
+
+    assert_eq!(context(&before).block_time, 1);
+    assert_eq!(context(&after).block_time, 4);
+    assert_eq!(simulator.block_time(), 4);
+    assert_eq!(
+        context(&after).block_production_time,
+        4 * BLOCK_INTERVAL_MILLIS
+    );
+}
+
+#[test]
+fn calls_are_routed_to_the_named_contract() {
+    let (mut simulator, counter) = deployed();
+    let caller = deploy(&mut simulator, "caller", &caller_wasm());
+
+    let receipt = simulator
+        .execute(&Transaction::new(ALICE, caller, "forward", (5u64,)))
+        .unwrap();
+
+    assert_eq!(receipt.result, Ok(()));
+    assert_eq!(count(&simulator, counter), 5);
+    let call = &receipt.spawned[0];
+    assert_eq!(call.contract, counter);
+    assert_eq!(call.result, Ok(()));
+    let context = context(call);
+    assert_eq!(context.sender, caller);
+    assert_eq!(context.current_transaction, call.transaction);
+    assert_eq!(context.original_transaction, receipt.transaction);
+    assert_ne!(call.transaction, receipt.transaction);
+}
+
+#[test]
+fn callers_are_called_back() {
+    let (mut simulator, _) = deployed();
+    let caller = deploy(&mut simulator, "caller", &caller_wasm());
+
+    let receipt = simulator
+        .execute(&Transaction::new(ALICE, caller, "forward", (5u64,)))
+        .unwrap();
+
+    assert_eq!(receipt.spawned.len(), 2);
+    let callback = &receipt.spawned[1];
+    assert_eq!(callback.contract, caller);
+    assert_eq!(callback.result, Ok(()));
+    // One callback, for a successful call.
+    assert_eq!(count(&simulator, caller), 1);
+}
+
+#[test]
+fn failed_calls_do_not_revert_the_caller() {
+    let (mut simulator, counter) = deployed();
+    let caller = deploy(&mut simulator, "caller", &caller_wasm());
+
+    let receipt = simulator
+        .execute(&Transaction::new(ALICE, caller, "forward_failing", ()))
+        .unwrap();
+
+    assert_eq!(receipt.result, Ok(()));
+    let failure = receipt.spawned[0].result.clone().unwrap_err();
+    assert_eq!(failure.error::<String>(), Some(String::from("boom")));
+    assert_eq!(count(&simulator, counter), 0);
+    // One callback, for a failed call.
+    assert_eq!(count(&simulator, caller), 0x101);
+}
+
+#[test]
+fn calls_to_missing_contracts_are_called_back_with_an_error() {
+    let (mut simulator, _) = deployed();
+    let caller = deploy(&mut simulator, "caller", &caller_wasm());
+
+    let receipt = simulator
+        .execute(&Transaction::new(ALICE, caller, "forward_missing", ()))
+        .unwrap();
+
+    assert_eq!(receipt.spawned.len(), 1);
+    assert_eq!(receipt.spawned[0].contract, caller);
+    assert_eq!(count(&simulator, caller), 0x101);
+}
+
+#[test]
+fn failed_transactions_are_reverted() {
+    let (mut simulator, counter) = deployed();
+    simulator
+        .execute(&Transaction::new(OWNER, counter, "increment", (2u64,)))
+        .unwrap();
+
+    let receipt = simulator
+        .execute(&Transaction::new(OWNER, counter, "fail", ()))
+        .unwrap();
+
+    let failure = receipt.result.unwrap_err();
+    assert_eq!(failure.error::<String>(), Some(String::from("boom")));
+    assert_eq!(receipt.logs, vec![String::from("failing")]);
+    assert_eq!(count(&simulator, counter), 2);
+}
+
+#[test]
+fn traps_are_reported_as_failures() {
+    let (mut simulator, counter) = deployed();
+
+    let receipt = simulator
+        .execute(&Transaction::new(OWNER, counter, "crash", ()))
+        .unwrap();
+
+    let failure = receipt.result.unwrap_err();
+    assert_eq!(failure.error::<String>(), None);
+    assert!(failure.message().starts_with("`action_3` trapped"));
+    assert_eq!(count(&simulator, counter), 0);
+}
+
+#[test]
+fn reads_outside_the_memory_of_the_contract_trap() {
+    let (mut simulator, counter) = deployed();
+
+    for (action, reason) in [
+        ("log_negative", "Negative length -1"),
+        (
+            "log_out_of_bounds",
+            "The 100 bytes at 65530 are out of the bounds",
+        ),
+        ("read_context_negative", "Negative length -1"),
+        (
+            "return_out_of_bounds",
+            "The 4 bytes at 65534 are out of the bounds",
+        ),
+        (
+            "return_high_address",
+            "The 4 bytes at 2147483648 are out of the bounds",
+        ),
+    ] {
+        let receipt = simulator
+            .execute(&Transaction::new(OWNER, counter, action, ()))
+            .unwrap();
+
+        let message = receipt.result.unwrap_err().message();
+        assert!(message.contains(reason), "{}: {}", action, message);
+    }
+    assert_eq!(count(&simulator, counter), 0);
+}
+
+#[test]
+fn malformed_results_abort_the_invocation() {
+    let (mut simulator, counter) = deployed();
+
+    for (action, reason) in [
+        ("return_empty", "The result buffer ends unexpectedly"),
+        ("return_unknown_status", "Unrecognized result status 7"),
+        ("return_truncated", "The result buffer ends unexpectedly"),
+    ] {
+        let receipt = simulator
+            .execute(&Transaction::new(OWNER, counter, action, ()))
+            .unwrap();
+
+        assert_eq!(
+            receipt.result,
+            Err(Failure::Aborted(String::from(reason))),
+            "{}",
+            action
+        );
+    }
+    assert_eq!(count(&simulator, counter), 0);
+}
+
+#[test]
+fn unknown_contracts_and_actions_are_rejected() {
+    let (mut simulator, counter) = deployed();
+
+    let unknown_action = simulator.execute(&Transaction::new(OWNER, counter, "reset", ()));
+    let unknown_contract = simulator.execute(&Transaction::new(OWNER, ALICE, "increment", ()));
+
+    assert_eq!(
+        unknown_action,
+        Err(String::from("The contract has no action `reset`"))
+    );
+    assert!(unknown_contract
+        .unwrap_err()
+        .starts_with("No contract is deployed at"));
+}
+
+#[test]
+fn invocations_report_the_fuel_they_consume() {
+    let (mut simulator, counter) = deployed();
+    let increment = Transaction::new(OWNER, counter, "increment", (1u64,));
+
+    let first = simulator.execute(&increment).unwrap();
+    let second = simulator.execute(&increment).unwrap();
+
+    assert!(first.fuel > 0);
+    assert_eq!(first.fuel, second.fuel);
+}
+
+#[test]
+fn invocations_beyond_the_fuel_limit_are_aborted() {
+    let (mut simulator, counter) = deployed();
+    let increment = Transaction::new(OWNER, counter, "increment", (1u64,));
+    let fuel = simulator.execute(&increment).unwrap().fuel;
+
+    simulator.set_fuel_limit(fuel - 1);
+    let aborted = simulator.execute(&increment).unwrap();
+    simulator.set_fuel_limit(fuel);
+    let completed = simulator.execute(&increment).unwrap();
+
+    assert_eq!(
+        aborted.result,
+        Err(Failure::Aborted(String::from("`action_1` ran out of fuel")))
+    );
+    assert!(aborted.fuel < fuel);
+    assert_eq!(completed.result, Ok(()));
+    assert_eq!(count(&simulator, counter), 2);
+}
+
+#[test]
+fn total_fuel_includes_the_spawned_interactions() {
+    let (mut simulator, _) = deployed();
+    let caller = deploy(&mut simulator, "caller", &caller_wasm());
+
+    let receipt = simulator
+        .execute(&Transaction::new(ALICE, caller, "forward", (5u64,)))
+        .unwrap();
+
+    let spawned: u64 = receipt.spawned.iter().map(|spawned| spawned.fuel).sum();
+    assert!(spawned > 0);
+    assert_eq!(receipt.total_fuel(), receipt.fuel + spawned);
+}
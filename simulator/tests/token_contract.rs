@@ -0,0 +1,85 @@
+//! The token contract compiled to wasm, run on the simulator.
+//!
+//! The contract is built for `wasm32-unknown-unknown` by the test itself, so the tests are
+//! ignored by default and need the target installed. Run them with
+//! `cargo test --test token_contract -- --ignored`.
+
+use std::path::Path;
+use std::process::Command;
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::serialization::ReadWrite;
+use pbc_simulator::{Simulator, Transaction};
+use token_contract::TokenContractState;
+
+const TARGET: &str = "wasm32-unknown-unknown";
+
+const OWNER: Address = Address::Account([1u8; 20]);
+const ALICE: Address = Address::Account([2u8; 20]);
+
+/// The token contract compiled to wasm.
+///
+/// Panics if the contract does not build, such as when the wasm target is not installed.
+fn token_wasm() -> Vec<u8> {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("../token-contract/Cargo.toml");
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("token-contract");
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .args(["--target", TARGET])
+        .status()
+        .unwrap();
+    assert!(
+        status.success(),
+        "The token contract does not build for {0}, install the target with `rustup target add {0}`",
+        TARGET
+    );
+
+    let wasm = target_dir.join(TARGET).join("debug/token_contract.wasm");
+    std::fs::read(wasm).unwrap()
+}
+
+fn state(simulator: &Simulator, token: Address) -> TokenContractState {
+    TokenContractState::read_from(&mut simulator.state(token).unwrap())
+}
+
+#[test]
+#[ignore = "builds the token contract, which needs the wasm32-unknown-unknown target"]
+fn token_contract_mints_transfers_and_reports_balances() {
+    let wasm = token_wasm();
+    let mut simulator = Simulator::new();
+
+    let deployed = simulator
+        .deploy("token", &wasm, OWNER, (None::<TokenContractState>,))
+        .unwrap();
+    assert_eq!(deployed.result, Ok(()));
+    let token = deployed.contract;
+
+    let receipts = simulator
+        .run_script(&[
+            Transaction::new(OWNER, token, "mint", (100u64,)),
+            Transaction::new(OWNER, token, "transfer", (ALICE, 30u64)),
+            Transaction::new(ALICE, token, "transfer", (OWNER, 31u64)),
+        ])
+        .unwrap();
+
+    assert_eq!(receipts[0].result, Ok(()));
+    assert_eq!(receipts[1].result, Ok(()));
+    let failure = receipts[2].result.clone().unwrap_err();
+    assert_eq!(
+        failure.error::<String>(),
+        Some(String::from("Insufficient balance: 30 < 31"))
+    );
+    assert_eq!(state(&simulator, token).total_supply, 100);
+
+    let state_before = simulator.state(token).unwrap().to_vec();
+    let owner_balance: u64 = simulator.view(token, "get_balance", (OWNER,)).unwrap();
+    let alice_balance: u64 = simulator.view(token, "get_balance", (ALICE,)).unwrap();
+
+    assert_eq!(owner_balance, 70);
+    assert_eq!(alice_balance, 30);
+    assert_eq!(simulator.state(token).unwrap(), state_before.as_slice());
+}
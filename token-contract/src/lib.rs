@@ -1,138 +1,142 @@
-#[macro_use]
-extern crate pbc_contract_codegen;
-extern crate pbc_contract_common;
-extern crate reflection;
-#[macro_use]
-extern crate reflection_derive;
-
-use std::collections::BTreeMap;
-//use std::convert::TryInto;
-use std::fmt::write;
-use std::io::{Read, Write};
-use std::ptr::null;
-
-use reflection::Reflection;
-
-use pbc_contract_common::address::Address;
-use pbc_contract_common::context::ContractContext;
-use pbc_contract_common::serialization::{ReadInt, ReadWrite, WriteInt};
-
-#[state]
-pub struct TokenContractState {
-    symbol: [u8; 16],
-    total_supply: u64,
-    balances: BTreeMap<Address, u64>,
-}
-
-impl TokenContractState {
-    fn update_balance(&mut self, address: Address, delta: i64) {
-        // Your code here
-        let wallet_balance: u64 = match self.balances.get(&address) {
-            Some(balance) => *balance,
-            None => 0,
-        };
+use pbc_contract::prelude::*;
 
-        self.balances.insert(address, wallet_balance + delta as u64);
+#[contract]
+mod token {
+    use std::collections::BTreeMap;
 
-        self.total_supply += delta as u64;
+    use super::*;
 
+    #[state]
+    pub struct TokenContractState {
+        pub symbol: [u8; 16],
+        pub total_supply: u64,
+        pub balances: BTreeMap<Address, u64>,
     }
 
-    fn get_balance(&self, address: Address) -> u64 {
-        // Your code here
-        let wallet_balance = match self.balances.get(&address) {
-            Some(balance) => *balance,
-            None => 0,
-        };
+    #[event]
+    pub struct Minted {
+        pub to: Address,
+        pub amount: u64,
+    }
 
-        wallet_balance
+    #[event]
+    pub struct Transferred {
+        pub from: Address,
+        pub to: Address,
+        pub amount: u64,
     }
-}
 
-impl ReadWrite for TokenContractState {
-    fn read_from<T: Read>(reader: &mut T) -> Self {
-        // Your code here
-        let mut _symbol = [0u8; 16];
-        reader.read_exact(&mut _symbol).unwrap();
-        let mut _balances = BTreeMap::new();
-        _balances = BTreeMap::read_from(reader);
-        Self {
-            symbol: _symbol,
-            total_supply: reader.read_u64_be(),
-            balances: _balances,
+    #[contract]
+    impl TokenContractState {
+        #[init]
+        pub fn initialize(_ctx: ContractContext, base_state: Option<TokenContractState>) -> Self {
+            let mut _symbol = [0u8; 16];
+            let mut _total_supply: u64 = 0;
+            let mut _balances = BTreeMap::new();
+            match base_state {
+                None => {}
+                Some(TokenContractState {
+                    symbol,
+                    total_supply,
+                    balances,
+                }) => {
+                    _symbol = symbol;
+                    _total_supply = total_supply;
+                    _balances = balances;
+                }
+            }
+            TokenContractState {
+                symbol: _symbol,
+                total_supply: _total_supply,
+                balances: _balances,
+            }
         }
-    }
 
-    fn write_to<T: Write + WriteInt>(&self, writer: &mut T) -> std::io::Result<()> {
-       // Your code here
-       // self.symbol.write_to(writer)?;
-       let _symbol = self.symbol.clone();
-       writer.write(&_symbol)?;
-       self.total_supply.write_to(writer)?;
-       self.balances.write_to(writer)
-    }
-}
+        #[action(only_owner)]
+        pub fn mint(&mut self, context: ContractContext, amount: u64) {
+            self.credit(context.sender, amount);
+            emit(&Minted {
+                to: context.sender,
+                amount,
+            });
+        }
 
-#[init]
-pub fn initialize(
-    ctx: ContractContext,
-    base_state: Option<TokenContractState>,
-) -> TokenContractState {
-   // Your code here
-    let mut _symbol = [0u8; 16];
-    let mut _total_supply:u64 = 0;
-    let mut _balances = BTreeMap::new();
-    match base_state {
-        None => {},
-        Some(TokenContractState { symbol, total_supply, balances }) => {
-            _symbol = symbol;
-            _total_supply = total_supply;
-            _balances = balances;
-        },
-    }
-    TokenContractState { symbol: _symbol, total_supply: _total_supply, balances: _balances }
-}
+        #[action]
+        pub fn transfer(
+            &mut self,
+            context: ContractContext,
+            dest: Address,
+            amount: u64,
+        ) -> Result<(), String> {
+            let sender = context.sender;
+            let sender_balance = self.balance_of(sender);
+
+            // Throw an error if the sender does not have enough balance.
+            if sender_balance < amount {
+                return Err(format!(
+                    "Insufficient balance: {} < {}",
+                    sender_balance, amount
+                ));
+            }
+
+            // Modify sender balance.
+            self.debit(sender, amount);
+
+            // Modify dest balance.
+            self.credit(dest, amount);
+            emit(&Transferred {
+                from: sender,
+                to: dest,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        #[view]
+        pub fn get_balance(&self, _context: ContractContext, address: Address) -> u64 {
+            self.balance_of(address)
+        }
+
+        /// Add `amount` to the balance of `address` and to the total supply.
+        ///
+        /// Panics if either overflows, which aborts the invocation.
+        fn credit(&mut self, address: Address, amount: u64) {
+            let balance = self
+                .balance_of(address)
+                .checked_add(amount)
+                .expect("The balance overflows u64");
+            self.total_supply = self
+                .total_supply
+                .checked_add(amount)
+                .expect("The total supply overflows u64");
+            self.balances.insert(address, balance);
+        }
 
-#[action]
-pub fn mint(
-    context: ContractContext,
-    state: TokenContractState,
-    amount: u64,
-) -> TokenContractState {
-   // Your code here
-    let mut _state = state.clone();
-    if context.owner != context.sender {
-        return _state
+        /// Remove `amount` from the balance of `address` and from the total supply.
+        ///
+        /// Panics if either is smaller than `amount`, which aborts the invocation.
+        fn debit(&mut self, address: Address, amount: u64) {
+            let balance = self
+                .balance_of(address)
+                .checked_sub(amount)
+                .expect("The balance is smaller than the debited amount");
+            self.total_supply = self
+                .total_supply
+                .checked_sub(amount)
+                .expect("The total supply is smaller than the debited amount");
+            self.balances.insert(address, balance);
+        }
+
+        fn balance_of(&self, address: Address) -> u64 {
+            let wallet_balance = match self.balances.get(&address) {
+                Some(balance) => *balance,
+                None => 0,
+            };
+
+            wallet_balance
+        }
     }
-    _state.update_balance(context.sender, amount as i64);
-    TokenContractState { symbol: _state.symbol, total_supply: _state.total_supply + amount, balances: _state.balances }
 }
 
-#[action]
-pub fn transfer(
-    context: ContractContext,
-    state: TokenContractState,
-    dest: Address,
-    amount: u64,
-) -> TokenContractState {
-   // Your code here
-   let sender = context.sender;
-   let mut _state = state.clone();
-   if context.owner != sender {
-       return _state
-   }
-   let sender_balance = _state.get_balance(sender);
-
-   // Throw an error if the sender does not have enough balance.
-   if sender_balance < amount {
-        return _state
-   }
-
-   // Modify sender balance.
-   _state.update_balance(sender, -(amount as i64));
-
-   // Modify dest balance.
-   _state.update_balance(dest, amount as i64);
-
-   TokenContractState {symbol: _state.symbol, total_supply: _state.total_supply, balances: _state.balances}
-}
+pub use token::*;
@@ -0,0 +1,123 @@
+use pbc_contract::prelude::*;
+use pbc_contract::test_utils::{EntryPoints, TestContract};
+use token_contract::{Minted, TokenContractState, Transferred};
+
+const OWNER: Address = Address::Account([1u8; 20]);
+const ALICE: Address = Address::Account([2u8; 20]);
+
+fn deploy() -> TestContract<TokenContractState> {
+    let entry_points = EntryPoints {
+        init: token_contract::init,
+        execute: token_contract::execute,
+        view: token_contract::view,
+        abi: token_contract::export_contract_schema_binary,
+    };
+    TestContract::deploy(entry_points, OWNER, (None::<TokenContractState>,)).unwrap()
+}
+
+fn balance_of(token: &TestContract<TokenContractState>, address: Address) -> u64 {
+    token.state().balances.get(&address).copied().unwrap_or(0)
+}
+
+#[test]
+fn mint_credits_the_owner() {
+    let mut token = deploy();
+
+    token.action("mint", (100u64,)).unwrap();
+
+    assert_eq!(token.state().total_supply, 100);
+    assert_eq!(balance_of(&token, OWNER), 100);
+    assert_eq!(
+        token.events::<Minted>(),
+        vec![Minted {
+            to: OWNER,
+            amount: 100
+        }]
+    );
+}
+
+#[test]
+fn mints_add_up_in_the_total_supply() {
+    let mut token = deploy();
+
+    token.action("mint", (100u64,)).unwrap();
+    token.action("mint", (50u64,)).unwrap();
+
+    assert_eq!(token.state().total_supply, 150);
+    assert_eq!(balance_of(&token, OWNER), 150);
+}
+
+#[test]
+fn amounts_above_i64_max_are_transferred_exactly() {
+    let mut token = deploy();
+    let amount = i64::MAX as u64 + 2;
+    token.action("mint", (amount,)).unwrap();
+
+    token.action("transfer", (ALICE, amount - 1)).unwrap();
+
+    assert_eq!(balance_of(&token, OWNER), 1);
+    assert_eq!(balance_of(&token, ALICE), amount - 1);
+    assert_eq!(token.state().total_supply, amount);
+}
+
+#[test]
+fn only_the_owner_can_mint() {
+    let mut token = deploy();
+    token.set_sender(ALICE);
+
+    let error = token.action("mint", (100u64,)).unwrap_err();
+
+    assert_eq!(
+        error,
+        "Only the owner of the contract can invoke this action"
+    );
+    assert_eq!(token.state().total_supply, 0);
+}
+
+#[test]
+fn transfer_moves_balance_between_accounts() {
+    let mut token = deploy();
+    token.action("mint", (100u64,)).unwrap();
+
+    token.action("transfer", (ALICE, 30u64)).unwrap();
+
+    assert_eq!(balance_of(&token, OWNER), 70);
+    assert_eq!(balance_of(&token, ALICE), 30);
+    assert_eq!(token.state().total_supply, 100);
+    assert_eq!(
+        token.events::<Transferred>(),
+        vec![Transferred {
+            from: OWNER,
+            to: ALICE,
+            amount: 30
+        }]
+    );
+}
+
+#[test]
+fn transfer_fails_without_enough_balance() {
+    let mut token = deploy();
+    token.action("mint", (100u64,)).unwrap();
+    let state_before = token.state();
+    token.set_sender(ALICE);
+
+    let error = token.action("transfer", (OWNER, 1u64)).unwrap_err();
+
+    assert_eq!(error, "Insufficient balance: 0 < 1");
+    assert_eq!(token.state(), state_before);
+}
+
+#[test]
+fn get_balance_reads_the_balance_without_changing_the_state() {
+    let mut token = deploy();
+    token.action("mint", (100u64,)).unwrap();
+    token.action("transfer", (ALICE, 30u64)).unwrap();
+    let state_before = token.state_bytes().to_vec();
+
+    let owner_balance: u64 = token.view("get_balance", (OWNER,));
+    let alice_balance: u64 = token.view("get_balance", (ALICE,));
+
+    assert_eq!(owner_balance, 70);
+    assert_eq!(alice_balance, 30);
+    assert_eq!(token.state_bytes(), state_before.as_slice());
+}